@@ -1,16 +1,268 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::cgroup::{CgroupStats, Owner};
+use crate::docker::ContainerInfo;
+use crate::system::{DiskIo, InterfaceDetails, InterfaceIo, ProcessDetails, ProcessInfo, ProtocolCounters, Sample};
 
 #[derive(Default)]
 pub enum ConfirmDialog {
     #[default]
     None,
-    KillProcess {
-        port: u16,
+    Open {
+        pid: u32,
+        port: Option<u16>,
         process_name: String,
-        selected_yes: bool,
+        /// What the process belongs to ("container web", "unit nginx.service"), if anything
+        owner: Option<String>,
+        actions: Vec<Action>,
+        /// 0 is "No", otherwise `actions[selected - 1]`
+        selected: usize,
     },
 }
 
+/// What confirming the dialog does.
+#[derive(Clone)]
+pub enum Action {
+    Kill(u32),
+    StopContainer { id: String, name: String },
+    RestartContainer { id: String, name: String },
+    StopUnit { name: String, user: bool },
+    RestartUnit { name: String, user: bool },
+}
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Kill(_) => "Kill",
+            Action::StopContainer { .. } | Action::StopUnit { .. } => "Stop",
+            Action::RestartContainer { .. } | Action::RestartUnit { .. } => "Restart",
+        }
+    }
+}
+
+/// Top-level screen, switched with the number keys.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Dashboard,
+    Processes,
+    Cgroups,
+    Users,
+}
+
+impl View {
+    pub const ALL: [View; 4] = [View::Dashboard, View::Processes, View::Cgroups, View::Users];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            View::Dashboard => "Dashboard",
+            View::Processes => "Processes",
+            View::Cgroups => "Cgroups",
+            View::Users => "Users",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSort {
+    Pid,
+    User,
+    Cpu,
+    Memory,
+    State,
+    Threads,
+    Sockets,
+    Owner,
+    Command,
+}
+
+impl ProcessSort {
+    pub const ALL: [ProcessSort; 9] = [
+        ProcessSort::Pid,
+        ProcessSort::User,
+        ProcessSort::Cpu,
+        ProcessSort::Memory,
+        ProcessSort::State,
+        ProcessSort::Threads,
+        ProcessSort::Sockets,
+        ProcessSort::Owner,
+        ProcessSort::Command,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ProcessSort::Pid => "PID",
+            ProcessSort::User => "User",
+            ProcessSort::Cpu => "CPU%",
+            ProcessSort::Memory => "Memory",
+            ProcessSort::State => "State",
+            ProcessSort::Threads => "Threads",
+            ProcessSort::Sockets => "Sockets",
+            ProcessSort::Owner => "Owner",
+            ProcessSort::Command => "Command",
+        }
+    }
+
+    fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&s| s == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    fn compare(self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        let ord = match self {
+            ProcessSort::Pid => a.pid.cmp(&b.pid),
+            ProcessSort::User => a.user.cmp(&b.user),
+            ProcessSort::Cpu => a.cpu.total_cmp(&b.cpu),
+            ProcessSort::Memory => a.memory.cmp(&b.memory),
+            ProcessSort::State => a.state.cmp(&b.state),
+            ProcessSort::Threads => a.threads.cmp(&b.threads),
+            ProcessSort::Sockets => a.sockets.total.cmp(&b.sockets.total),
+            ProcessSort::Owner => compare_owners(&a.owner, &b.owner),
+            ProcessSort::Command => a.command.cmp(&b.command),
+        };
+        ord.then(a.pid.cmp(&b.pid))
+    }
+}
+
+#[derive(Default)]
+pub enum DetailPopup {
+    #[default]
+    None,
+    Interface(InterfaceDetails),
+    Process(Box<ProcessDetails>),
+}
+
+/// Dashboard panels, selectable with `--panels`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Panel {
+    Cpu,
+    Memory,
+    Tcp,
+    Disk,
+    Network,
+    Ports,
+}
+
+/// Panel that receives navigation keys.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Focus {
+    Ports,
+    Network,
+    /// Neither focusable panel is shown
+    None,
+}
+
+/// Order owners alphabetically with unowned processes last.
+fn compare_owners(a: &Option<Owner>, b: &Option<Owner>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Position of a row in the process tree, parallel to `App::process_view` in tree mode.
+pub struct TreeRow {
+    pub depth: usize,
+    pub has_children: bool,
+    pub collapsed: bool,
+    /// CPU and memory of the process plus all of its descendants
+    pub subtree_cpu: f32,
+    pub subtree_memory: u64,
+}
+
+/// Number of samples kept for the history graphs (one per tick).
+pub const HISTORY_LEN: usize = 60;
+
+pub struct InterfaceRate {
+    pub name: String,
+    pub rx: u64,
+    pub tx: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+}
+
+impl InterfaceRate {
+    fn between(prev: Option<&InterfaceIo>, curr: &InterfaceIo, secs: f64) -> Self {
+        let delta = |f: fn(&InterfaceIo) -> u64| prev.map_or(0, |p| per_second(f(curr).saturating_sub(f(p)), secs));
+        Self {
+            name: curr.name.clone(),
+            rx: delta(|i| i.rx_bytes),
+            tx: delta(|i| i.tx_bytes),
+            rx_packets: delta(|i| i.rx_packets),
+            tx_packets: delta(|i| i.tx_packets),
+            rx_errors: delta(|i| i.rx_errors),
+            tx_errors: delta(|i| i.tx_errors),
+            rx_drops: delta(|i| i.rx_drops),
+            tx_drops: delta(|i| i.tx_drops),
+        }
+    }
+
+    pub fn has_faults(&self) -> bool {
+        self.rx_errors + self.tx_errors + self.rx_drops + self.tx_drops > 0
+    }
+}
+
+/// Processes of one user added up.
+pub struct UserSummary {
+    pub name: String,
+    pub uid: Option<u32>,
+    pub processes: usize,
+    pub cpu: f32,
+    pub memory: u64,
+    /// Ports listened on by the user's processes
+    pub ports: Vec<u16>,
+}
+
+/// Per-second usage of one cgroup, with its limits.
+pub struct CgroupRate {
+    pub path: String,
+    pub depth: usize,
+    /// Percent of one core, like the process table
+    pub cpu: f64,
+    pub cpu_max: Option<f64>,
+    pub memory_current: Option<u64>,
+    pub memory_max: Option<u64>,
+    pub io_read: u64,
+    pub io_write: u64,
+}
+
+impl CgroupRate {
+    fn between(prev: Option<&CgroupStats>, curr: &CgroupStats, secs: f64) -> Self {
+        let delta = |f: fn(&CgroupStats) -> u64| prev.map_or(0, |p| per_second(f(curr).saturating_sub(f(p)), secs));
+        Self {
+            path: curr.path.clone(),
+            depth: curr.depth,
+            cpu: delta(|c| c.cpu_usage_usec) as f64 / 10_000.0,
+            cpu_max: curr.cpu_max,
+            memory_current: curr.memory_current,
+            memory_max: curr.memory_max,
+            io_read: delta(|c| c.io_read_bytes),
+            io_write: delta(|c| c.io_write_bytes),
+        }
+    }
+
+    /// Last path component, the name shown in the tree.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').find(|s| !s.is_empty()).unwrap_or("/")
+    }
+}
+
+/// Where `dashy replay` is in its recording, for the status line.
+pub struct ReplayStatus {
+    pub host: Option<String>,
+    /// Index of the sample on screen
+    pub position: usize,
+    pub len: usize,
+    pub paused: bool,
+    pub speed: f64,
+}
+
 pub struct App {
     pub cpu_history: VecDeque<f64>,
     pub memory_used: u64,
@@ -19,74 +271,801 @@ pub struct App {
     pub disk_write: u64,
     pub network_rx: u64,
     pub network_tx: u64,
+    pub network_interfaces: Vec<InterfaceRate>,
+    pub disk_read_history: VecDeque<u64>,
+    pub disk_write_history: VecDeque<u64>,
+    pub network_rx_history: VecDeque<u64>,
+    pub network_tx_history: VecDeque<u64>,
+    pub protocol_rates: ProtocolCounters,
+    pub protocol_history: VecDeque<ProtocolCounters>,
+    /// Unix time in milliseconds of each history entry
+    pub history_times: VecDeque<u64>,
+    pub memory_history: VecDeque<u64>,
+    pub cpu_core_history: VecDeque<Vec<f32>>,
+    /// (disk, read, write) in bytes/sec
+    pub disk_device_history: VecDeque<Vec<(String, u64, u64)>>,
+    /// (interface, rx, tx) in bytes/sec
+    pub interface_history: VecDeque<Vec<(String, u64, u64)>>,
     pub open_ports: Vec<(u16, String, u32)>, // (port, process_name, pid)
     pub should_quit: bool,
     pub selected_port_idx: usize,
     pub confirm_dialog: ConfirmDialog,
+    pub focus: Focus,
+    pub selected_interface_idx: usize,
+    pub detail_popup: DetailPopup,
+    pub detail_scroll: u16,
+    pub ports_wide: bool,
+    /// Dashboard panels to draw, all of them if empty
+    pub panels: Vec<Panel>,
+    pub view: View,
+    pub processes: Vec<ProcessInfo>,
+    /// Indices into `processes` after filtering and sorting, in display order
+    pub process_view: Vec<usize>,
+    pub selected_process_idx: usize,
+    pub process_sort: ProcessSort,
+    pub process_sort_desc: bool,
+    pub process_filter: String,
+    /// Only show this user's processes (drill-down from the users view)
+    pub user_filter: Option<String>,
+    pub filter_editing: bool,
+    pub process_tree: bool,
+    /// Keep processes and ports of the same container/pod/unit together
+    pub group_by_owner: bool,
+    pub tree_rows: Vec<TreeRow>,
+    /// Running containers, empty unless the Docker socket is reachable
+    pub containers: Vec<ContainerInfo>,
+    /// Outcome of the last dialog action, shown next to the tabs
+    pub status_message: Option<String>,
+    pub cgroups: Vec<CgroupRate>,
+    pub selected_cgroup_idx: usize,
+    /// Heaviest CPU users first
+    pub user_summaries: Vec<UserSummary>,
+    pub selected_user_idx: usize,
+    /// Set while replaying a recording instead of watching this machine
+    pub replay: Option<ReplayStatus>,
+    /// Frozen: new samples are held back instead of shown
+    pub paused: bool,
+    pub held_samples: VecDeque<Sample>,
+    collapsed_pids: HashSet<u32>,
     // Previous values for delta calculation
+    prev_timestamp_ms: Option<u64>,
+    /// The oldest history entry is the first sample, which has no rates yet
+    baseline_in_history: bool,
     prev_disk_read: u64,
     prev_disk_write: u64,
-    prev_network_rx: u64,
-    prev_network_tx: u64,
+    prev_disk_devices: HashMap<String, DiskIo>,
+    prev_interfaces: HashMap<String, InterfaceIo>,
+    prev_protocols: Option<ProtocolCounters>,
+    prev_cgroups: HashMap<String, CgroupStats>,
 }
 
 impl App {
     pub fn new() -> Self {
         Self {
-            cpu_history: VecDeque::with_capacity(60),
+            cpu_history: VecDeque::with_capacity(HISTORY_LEN),
             memory_used: 0,
             memory_total: 0,
             disk_read: 0,
             disk_write: 0,
             network_rx: 0,
             network_tx: 0,
+            network_interfaces: Vec::new(),
+            disk_read_history: VecDeque::with_capacity(HISTORY_LEN),
+            disk_write_history: VecDeque::with_capacity(HISTORY_LEN),
+            network_rx_history: VecDeque::with_capacity(HISTORY_LEN),
+            network_tx_history: VecDeque::with_capacity(HISTORY_LEN),
+            protocol_rates: ProtocolCounters::default(),
+            protocol_history: VecDeque::with_capacity(HISTORY_LEN),
+            history_times: VecDeque::with_capacity(HISTORY_LEN),
+            memory_history: VecDeque::with_capacity(HISTORY_LEN),
+            cpu_core_history: VecDeque::with_capacity(HISTORY_LEN),
+            disk_device_history: VecDeque::with_capacity(HISTORY_LEN),
+            interface_history: VecDeque::with_capacity(HISTORY_LEN),
             open_ports: Vec::new(),
             should_quit: false,
             selected_port_idx: 0,
             confirm_dialog: ConfirmDialog::None,
+            focus: Focus::Ports,
+            selected_interface_idx: 0,
+            detail_popup: DetailPopup::None,
+            detail_scroll: 0,
+            ports_wide: false,
+            panels: Vec::new(),
+            view: View::Dashboard,
+            processes: Vec::new(),
+            process_view: Vec::new(),
+            selected_process_idx: 0,
+            process_sort: ProcessSort::Cpu,
+            process_sort_desc: true,
+            process_filter: String::new(),
+            user_filter: None,
+            filter_editing: false,
+            process_tree: false,
+            group_by_owner: false,
+            tree_rows: Vec::new(),
+            containers: Vec::new(),
+            status_message: None,
+            cgroups: Vec::new(),
+            selected_cgroup_idx: 0,
+            user_summaries: Vec::new(),
+            selected_user_idx: 0,
+            replay: None,
+            paused: false,
+            held_samples: VecDeque::new(),
+            collapsed_pids: HashSet::new(),
+            prev_timestamp_ms: None,
+            baseline_in_history: false,
             prev_disk_read: 0,
             prev_disk_write: 0,
-            prev_network_rx: 0,
-            prev_network_tx: 0,
+            prev_disk_devices: HashMap::new(),
+            prev_interfaces: HashMap::new(),
+            prev_protocols: None,
+            prev_cgroups: HashMap::new(),
         }
     }
 
-    pub fn update(&mut self, cpu: f64, memory: (u64, u64), disk: (u64, u64), network: (u64, u64), ports: Vec<(u16, String, u32)>) {
-        // Update CPU history
-        if self.cpu_history.len() >= 60 {
-            self.cpu_history.pop_front();
+    pub fn update(&mut self, sample: Sample) {
+        let Sample {
+            timestamp_ms,
+            cpu,
+            cpu_cores,
+            memory,
+            disk,
+            disk_devices,
+            network,
+            protocols,
+            processes,
+            ports,
+            cgroups,
+        } = sample;
+
+        // Counters are turned into per-second rates over the actual time between samples
+        let secs = self
+            .prev_timestamp_ms
+            .map(|prev| timestamp_ms.saturating_sub(prev) as f64 / 1000.0)
+            .filter(|&secs| secs > 0.0)
+            .unwrap_or(1.0);
+        // The first sample stays on the graphs until it scrolls off
+        if self.prev_timestamp_ms.is_none() {
+            self.baseline_in_history = true;
+        } else if self.history_times.len() >= HISTORY_LEN {
+            self.baseline_in_history = false;
         }
-        self.cpu_history.push_back(cpu);
+        self.prev_timestamp_ms = Some(timestamp_ms);
+
+        // Update CPU history
+        push_history(&mut self.history_times, timestamp_ms);
+        push_history(&mut self.cpu_history, cpu);
+        push_history(&mut self.cpu_core_history, cpu_cores);
 
         // Update memory
         self.memory_used = memory.0;
         self.memory_total = memory.1;
+        push_history(&mut self.memory_history, memory.0);
 
         // Calculate disk delta (bytes/sec)
         let (curr_disk_read, curr_disk_write) = disk;
         if self.prev_disk_read > 0 {
-            self.disk_read = curr_disk_read.saturating_sub(self.prev_disk_read);
-            self.disk_write = curr_disk_write.saturating_sub(self.prev_disk_write);
+            self.disk_read = per_second(curr_disk_read.saturating_sub(self.prev_disk_read), secs);
+            self.disk_write = per_second(curr_disk_write.saturating_sub(self.prev_disk_write), secs);
         }
         self.prev_disk_read = curr_disk_read;
         self.prev_disk_write = curr_disk_write;
+        push_history(&mut self.disk_read_history, self.disk_read);
+        push_history(&mut self.disk_write_history, self.disk_write);
+        let device_rates = disk_devices
+            .iter()
+            .map(|device| {
+                let (read, write) = match self.prev_disk_devices.get(&device.name) {
+                    Some(prev) => (
+                        per_second(device.read_bytes.saturating_sub(prev.read_bytes), secs),
+                        per_second(device.write_bytes.saturating_sub(prev.write_bytes), secs),
+                    ),
+                    None => (0, 0),
+                };
+                (device.name.clone(), read, write)
+            })
+            .collect();
+        push_history(&mut self.disk_device_history, device_rates);
+        self.prev_disk_devices = disk_devices.into_iter().map(|device| (device.name.clone(), device)).collect();
 
-        // Calculate network delta (bytes/sec)
-        let (curr_rx, curr_tx) = network;
-        if self.prev_network_rx > 0 {
-            self.network_rx = curr_rx.saturating_sub(self.prev_network_rx);
-            self.network_tx = curr_tx.saturating_sub(self.prev_network_tx);
+        // Calculate per-interface network delta (per second)
+        self.network_interfaces = network
+            .iter()
+            .map(|iface| InterfaceRate::between(self.prev_interfaces.get(&iface.name), iface, secs))
+            .collect();
+        self.prev_interfaces = network.into_iter().map(|iface| (iface.name.clone(), iface)).collect();
+        self.network_rx = self.network_interfaces.iter().map(|i| i.rx).sum();
+        self.network_tx = self.network_interfaces.iter().map(|i| i.tx).sum();
+        push_history(&mut self.network_rx_history, self.network_rx);
+        push_history(&mut self.network_tx_history, self.network_tx);
+        let interface_rates = self.network_interfaces.iter().map(|i| (i.name.clone(), i.rx, i.tx)).collect();
+        push_history(&mut self.interface_history, interface_rates);
+        if self.selected_interface_idx >= self.network_interfaces.len() && !self.network_interfaces.is_empty() {
+            self.selected_interface_idx = self.network_interfaces.len() - 1;
         }
-        self.prev_network_rx = curr_rx;
-        self.prev_network_tx = curr_tx;
+
+        // Calculate TCP/UDP counter deltas (per second)
+        if let Some(prev) = &self.prev_protocols {
+            let rate = |curr: u64, prev: u64| per_second(curr.saturating_sub(prev), secs);
+            self.protocol_rates = ProtocolCounters {
+                tcp_retransmits: rate(protocols.tcp_retransmits, prev.tcp_retransmits),
+                tcp_active_opens: rate(protocols.tcp_active_opens, prev.tcp_active_opens),
+                tcp_passive_opens: rate(protocols.tcp_passive_opens, prev.tcp_passive_opens),
+                tcp_estab_resets: rate(protocols.tcp_estab_resets, prev.tcp_estab_resets),
+                tcp_out_resets: rate(protocols.tcp_out_resets, prev.tcp_out_resets),
+                tcp_listen_overflows: rate(protocols.tcp_listen_overflows, prev.tcp_listen_overflows),
+                tcp_listen_drops: rate(protocols.tcp_listen_drops, prev.tcp_listen_drops),
+                udp_in_errors: rate(protocols.udp_in_errors, prev.udp_in_errors),
+                udp_rcvbuf_errors: rate(protocols.udp_rcvbuf_errors, prev.udp_rcvbuf_errors),
+            };
+        }
+        self.prev_protocols = Some(protocols);
+        push_history(&mut self.protocol_history, self.protocol_rates);
+
+        // Calculate per-cgroup CPU and I/O deltas (per second)
+        self.cgroups = cgroups
+            .iter()
+            .map(|cgroup| CgroupRate::between(self.prev_cgroups.get(&cgroup.path), cgroup, secs))
+            .collect();
+        self.prev_cgroups = cgroups.into_iter().map(|cgroup| (cgroup.path.clone(), cgroup)).collect();
+        if self.selected_cgroup_idx >= self.cgroups.len() {
+            self.selected_cgroup_idx = self.cgroups.len().saturating_sub(1);
+        }
+
+        self.set_processes(processes);
 
         // Update ports and adjust selection if needed
         self.open_ports = ports;
+        self.sort_ports();
+        self.rebuild_user_summaries();
         if self.selected_port_idx >= self.open_ports.len() && !self.open_ports.is_empty() {
             self.selected_port_idx = self.open_ports.len() - 1;
         }
     }
 
+    /// Forget the history graphs and the counters rates are computed from, so the next samples
+    /// start over as if freshly launched. Used when a replay jumps around in time.
+    pub fn clear_history(&mut self) {
+        self.cpu_history.clear();
+        self.disk_read_history.clear();
+        self.disk_write_history.clear();
+        self.network_rx_history.clear();
+        self.network_tx_history.clear();
+        self.protocol_history.clear();
+        self.history_times.clear();
+        self.memory_history.clear();
+        self.cpu_core_history.clear();
+        self.disk_device_history.clear();
+        self.interface_history.clear();
+        self.disk_read = 0;
+        self.disk_write = 0;
+        self.protocol_rates = ProtocolCounters::default();
+        self.prev_timestamp_ms = None;
+        self.prev_disk_read = 0;
+        self.prev_disk_write = 0;
+        self.prev_disk_devices.clear();
+        self.prev_interfaces.clear();
+        self.prev_protocols = None;
+        self.prev_cgroups.clear();
+    }
+
+    /// Index of the oldest history entry with rates, skipping the first sample while it's still
+    /// on the graphs: it only sets the baseline.
+    pub fn rated_history_start(&self) -> usize {
+        usize::from(self.baseline_in_history)
+    }
+
+    /// Unix time in milliseconds of the last sample applied.
+    pub fn sample_time_ms(&self) -> Option<u64> {
+        self.prev_timestamp_ms
+    }
+
+    pub fn set_ports(&mut self, ports: Vec<(u16, String, u32)>) {
+        self.open_ports = ports;
+        self.sort_ports();
+        self.rebuild_user_summaries();
+    }
+
+    fn rebuild_user_summaries(&mut self) {
+        let mut summaries: HashMap<&str, UserSummary> = HashMap::new();
+        let mut user_of: HashMap<u32, &str> = HashMap::new();
+        for process in &self.processes {
+            let summary = summaries.entry(&process.user).or_insert_with(|| UserSummary {
+                name: process.user.clone(),
+                uid: process.uid,
+                processes: 0,
+                cpu: 0.0,
+                memory: 0,
+                ports: Vec::new(),
+            });
+            summary.processes += 1;
+            summary.cpu += process.cpu;
+            summary.memory += process.memory;
+            user_of.insert(process.pid, &process.user);
+        }
+        for (port, _, pid) in &self.open_ports {
+            if let Some(summary) = user_of.get(pid).and_then(|user| summaries.get_mut(user)) {
+                summary.ports.push(*port);
+            }
+        }
+
+        let mut summaries: Vec<UserSummary> = summaries.into_values().collect();
+        for summary in &mut summaries {
+            summary.ports.sort_unstable();
+            summary.ports.dedup();
+        }
+        summaries.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(b.memory.cmp(&a.memory)).then(a.name.cmp(&b.name)));
+        self.user_summaries = summaries;
+        if self.selected_user_idx >= self.user_summaries.len() {
+            self.selected_user_idx = self.user_summaries.len().saturating_sub(1);
+        }
+    }
+
+    /// Show the selected user's processes in the process table.
+    pub fn drill_into_user(&mut self) {
+        if let Some(summary) = self.user_summaries.get(self.selected_user_idx) {
+            self.user_filter = Some(summary.name.clone());
+            self.view = View::Processes;
+            self.rebuild_process_view();
+        }
+    }
+
+    /// Ports arrive sorted by number; when grouping, order by owner first.
+    fn sort_ports(&mut self) {
+        if !self.group_by_owner {
+            self.open_ports.sort_by_key(|(port, _, _)| *port);
+            return;
+        }
+        let owners: HashMap<u32, &Option<Owner>> = self.processes.iter().map(|p| (p.pid, &p.owner)).collect();
+        self.open_ports.sort_by(|(port_a, _, pid_a), (port_b, _, pid_b)| {
+            let owner_a = owners.get(pid_a).copied().unwrap_or(&None);
+            let owner_b = owners.get(pid_b).copied().unwrap_or(&None);
+            compare_owners(owner_a, owner_b).then(port_a.cmp(port_b))
+        });
+    }
+
+    pub fn toggle_group_by_owner(&mut self) {
+        self.group_by_owner = !self.group_by_owner;
+        self.sort_ports();
+        self.rebuild_process_view();
+    }
+
+    pub fn set_processes(&mut self, processes: Vec<ProcessInfo>) {
+        self.processes = processes;
+        self.rebuild_process_view();
+    }
+
+    /// Freeze the screen, or unfreeze it and catch up on the samples held meanwhile so the
+    /// graphs have no gap.
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            for sample in std::mem::take(&mut self.held_samples) {
+                self.update(sample);
+            }
+        }
+    }
+
+    /// Keep a sample for when the pause ends. Older ones than the graphs show aren't needed.
+    pub fn hold(&mut self, sample: Sample) {
+        push_history(&mut self.held_samples, sample);
+    }
+
+    pub fn set_containers(&mut self, containers: Vec<ContainerInfo>) {
+        self.containers = containers;
+    }
+
+    /// The container a process runs in, from its cgroup owner.
+    pub fn container_of_owner(&self, owner: Option<&Owner>) -> Option<&ContainerInfo> {
+        let id = match owner? {
+            Owner::Container { id, .. } => id,
+            Owner::Pod { container: Some(id), .. } => id,
+            _ => return None,
+        };
+        self.containers.iter().find(|c| &c.id == id)
+    }
+
+    /// The container behind a listening port: the process's own container, or for a published
+    /// port the container it forwards to (the listener is then docker-proxy on the host).
+    pub fn container_of(&self, pid: u32, port: Option<u16>) -> Option<&ContainerInfo> {
+        let owner = self.processes.iter().find(|p| p.pid == pid).and_then(|p| p.owner.as_ref());
+        self.container_of_owner(owner).or_else(|| {
+            let port = port?;
+            self.containers.iter().find(|c| c.ports.iter().any(|p| p.host_port == port))
+        })
+    }
+
+    /// Owner column text: the container name when known, else the cgroup owner.
+    pub fn owner_label(&self, owner: Option<&Owner>) -> String {
+        match self.container_of_owner(owner) {
+            Some(container) => container.name.clone(),
+            None => owner.map(|o| o.to_string()).unwrap_or_default(),
+        }
+    }
+
+    /// Re-apply the filter and sort order, keeping the same process selected if it's still shown.
+    fn rebuild_process_view(&mut self) {
+        let selected_pid = self.selected_process().map(|p| p.pid);
+
+        let filter = self.process_filter.to_lowercase();
+        let matches: Vec<bool> = self
+            .processes
+            .iter()
+            .map(|p| {
+                let text_match = filter.is_empty()
+                    || p.name.to_lowercase().contains(&filter)
+                    || p.command.to_lowercase().contains(&filter);
+                text_match && self.user_filter.as_ref().is_none_or(|user| &p.user == user)
+            })
+            .collect();
+
+        if self.process_tree {
+            self.build_process_tree(&matches);
+        } else {
+            let processes = &self.processes;
+            let mut view: Vec<usize> = (0..processes.len()).filter(|&idx| matches[idx]).collect();
+
+            let (sort, desc, grouped) = (self.process_sort, self.process_sort_desc, self.group_by_owner);
+            view.sort_by(|&a, &b| {
+                let ord = sort.compare(&processes[a], &processes[b]);
+                let ord = if desc { ord.reverse() } else { ord };
+                if grouped {
+                    compare_owners(&processes[a].owner, &processes[b].owner).then(ord)
+                } else {
+                    ord
+                }
+            });
+            self.process_view = view;
+            self.tree_rows.clear();
+        }
+
+        self.selected_process_idx = selected_pid
+            .and_then(|pid| self.process_view.iter().position(|&idx| self.processes[idx].pid == pid))
+            .unwrap_or_else(|| self.selected_process_idx.min(self.process_view.len().saturating_sub(1)));
+    }
+
+    /// Lay the processes out depth-first under their parents. A process is shown if it or any
+    /// descendant matches the filter; children of collapsed processes are hidden.
+    fn build_process_tree(&mut self, matches: &[bool]) {
+        let processes = &self.processes;
+        let index_of: HashMap<u32, usize> = processes.iter().enumerate().map(|(idx, p)| (p.pid, idx)).collect();
+
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (idx, process) in processes.iter().enumerate() {
+            match process.parent.and_then(|ppid| index_of.get(&ppid)) {
+                Some(&parent_idx) if parent_idx != idx => children.entry(parent_idx).or_default().push(idx),
+                _ => roots.push(idx),
+            }
+        }
+
+        let (sort, desc) = (self.process_sort, self.process_sort_desc);
+        let sort_siblings = |siblings: &mut Vec<usize>| {
+            siblings.sort_by(|&a, &b| {
+                let ord = sort.compare(&processes[a], &processes[b]);
+                if desc { ord.reverse() } else { ord }
+            })
+        };
+        sort_siblings(&mut roots);
+        for siblings in children.values_mut() {
+            sort_siblings(siblings);
+        }
+
+        // Post-order pass: subtree totals and whether anything below matches the filter
+        let mut totals = vec![(0.0f32, 0u64, false); processes.len()];
+        fn accumulate(
+            idx: usize,
+            processes: &[ProcessInfo],
+            children: &HashMap<usize, Vec<usize>>,
+            matches: &[bool],
+            totals: &mut [(f32, u64, bool)],
+        ) {
+            let mut total = (processes[idx].cpu, processes[idx].memory, matches[idx]);
+            for &child in children.get(&idx).into_iter().flatten() {
+                accumulate(child, processes, children, matches, totals);
+                total.0 += totals[child].0;
+                total.1 += totals[child].1;
+                total.2 |= totals[child].2;
+            }
+            totals[idx] = total;
+        }
+        for &root in &roots {
+            accumulate(root, processes, &children, matches, &mut totals);
+        }
+
+        // Pre-order pass: emit visible rows
+        let mut view = Vec::new();
+        let mut rows = Vec::new();
+        let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&idx| (idx, 0)).collect();
+        while let Some((idx, depth)) = stack.pop() {
+            if !totals[idx].2 {
+                continue;
+            }
+            let kids = children.get(&idx);
+            let collapsed = self.collapsed_pids.contains(&processes[idx].pid);
+            view.push(idx);
+            rows.push(TreeRow {
+                depth,
+                has_children: kids.is_some_and(|k| !k.is_empty()),
+                collapsed,
+                subtree_cpu: totals[idx].0,
+                subtree_memory: totals[idx].1,
+            });
+            if !collapsed {
+                for &child in kids.into_iter().flatten().rev() {
+                    stack.push((child, depth + 1));
+                }
+            }
+        }
+
+        self.process_view = view;
+        self.tree_rows = rows;
+    }
+
+    pub fn toggle_process_tree(&mut self) {
+        self.process_tree = !self.process_tree;
+        self.rebuild_process_view();
+    }
+
+    /// Collapse (`Some(true)`), expand (`Some(false)`) or toggle (`None`) the selected subtree.
+    pub fn set_selected_collapsed(&mut self, collapsed: Option<bool>) {
+        if !self.process_tree {
+            return;
+        }
+        let Some(pid) = self.selected_process().map(|p| p.pid) else {
+            return;
+        };
+        let collapse = collapsed.unwrap_or(!self.collapsed_pids.contains(&pid));
+        if collapse {
+            self.collapsed_pids.insert(pid);
+        } else {
+            self.collapsed_pids.remove(&pid);
+        }
+        self.rebuild_process_view();
+    }
+
+    /// Open the process tree with `pid` visible and selected.
+    pub fn jump_to_process(&mut self, pid: u32) {
+        self.view = View::Processes;
+        self.process_tree = true;
+        self.process_filter.clear();
+        self.user_filter = None;
+
+        // Expand every ancestor so the process isn't hidden inside a collapsed subtree
+        let parents: HashMap<u32, Option<u32>> = self.processes.iter().map(|p| (p.pid, p.parent)).collect();
+        let mut ancestor = parents.get(&pid).copied().flatten();
+        // Bounded walk in case of a bogus parent cycle
+        for _ in 0..parents.len() {
+            let Some(ppid) = ancestor else {
+                break;
+            };
+            self.collapsed_pids.remove(&ppid);
+            ancestor = parents.get(&ppid).copied().flatten();
+        }
+
+        self.rebuild_process_view();
+        if let Some(idx) = self.process_view.iter().position(|&idx| self.processes[idx].pid == pid) {
+            self.selected_process_idx = idx;
+        }
+    }
+
+    pub fn selected_port_pid(&self) -> Option<u32> {
+        self.open_ports.get(self.selected_port_idx).map(|(_, _, pid)| *pid)
+    }
+
+    pub fn selected_process(&self) -> Option<&ProcessInfo> {
+        self.process_view
+            .get(self.selected_process_idx)
+            .map(|&idx| &self.processes[idx])
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.view = view;
+    }
+
+    pub fn cycle_process_sort(&mut self) {
+        self.set_process_sort(self.process_sort.next());
+    }
+
+    pub fn set_process_sort(&mut self, sort: ProcessSort) {
+        self.process_sort = sort;
+        // Numeric columns read best largest-first, text columns alphabetically
+        self.process_sort_desc = matches!(
+            self.process_sort,
+            ProcessSort::Cpu | ProcessSort::Memory | ProcessSort::Threads | ProcessSort::Sockets
+        );
+        self.rebuild_process_view();
+    }
+
+    pub fn reverse_process_sort(&mut self) {
+        self.process_sort_desc = !self.process_sort_desc;
+        self.rebuild_process_view();
+    }
+
+    pub fn start_filter(&mut self) {
+        self.filter_editing = true;
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.process_filter.push(c);
+        self.rebuild_process_view();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.process_filter.pop();
+        self.rebuild_process_view();
+    }
+
+    /// Leave filter input, keeping the filter unless `clear` is set.
+    pub fn finish_filter(&mut self, clear: bool) {
+        self.filter_editing = false;
+        if clear {
+            self.process_filter.clear();
+            self.rebuild_process_view();
+        }
+    }
+
+    /// Drop both the text filter and the user drill-down.
+    pub fn clear_filters(&mut self) {
+        self.process_filter.clear();
+        self.user_filter = None;
+        self.rebuild_process_view();
+    }
+
+    /// Moves focus to the first visible focusable panel if the focused one is hidden.
+    pub fn set_panels(&mut self, panels: Vec<Panel>) {
+        self.panels = panels;
+        let visible = match self.focus {
+            Focus::Ports => self.shows(Panel::Ports),
+            Focus::Network => self.shows(Panel::Network),
+            Focus::None => false,
+        };
+        if !visible {
+            self.focus = [(Panel::Ports, Focus::Ports), (Panel::Network, Focus::Network)]
+                .into_iter()
+                .find(|&(panel, _)| self.shows(panel))
+                .map_or(Focus::None, |(_, focus)| focus);
+        }
+    }
+
+    pub fn shows(&self, panel: Panel) -> bool {
+        self.panels.is_empty() || self.panels.contains(&panel)
+    }
+
+    /// Toggle the full-screen ports table with command lines wrapped instead of truncated.
+    pub fn toggle_ports_wide(&mut self) {
+        if !self.shows(Panel::Ports) {
+            return;
+        }
+        self.ports_wide = !self.ports_wide;
+        if self.ports_wide {
+            self.focus = Focus::Ports;
+        }
+    }
+
+    pub fn toggle_focus(&mut self) {
+        // Only the ports table is visible in wide mode
+        if self.ports_wide || !self.shows(Panel::Ports) || !self.shows(Panel::Network) {
+            return;
+        }
+        self.focus = match self.focus {
+            Focus::Ports => Focus::Network,
+            Focus::Network | Focus::None => Focus::Ports,
+        };
+    }
+
+    pub fn select_next(&mut self) {
+        if self.view == View::Users {
+            if !self.user_summaries.is_empty() {
+                self.selected_user_idx = (self.selected_user_idx + 1) % self.user_summaries.len();
+            }
+            return;
+        }
+        if self.view == View::Cgroups {
+            if !self.cgroups.is_empty() {
+                self.selected_cgroup_idx = (self.selected_cgroup_idx + 1) % self.cgroups.len();
+            }
+            return;
+        }
+        if self.view == View::Processes {
+            if !self.process_view.is_empty() {
+                self.selected_process_idx = (self.selected_process_idx + 1) % self.process_view.len();
+            }
+            return;
+        }
+        match self.focus {
+            Focus::Ports => self.select_next_port(),
+            Focus::Network => {
+                if !self.network_interfaces.is_empty() {
+                    self.selected_interface_idx = (self.selected_interface_idx + 1) % self.network_interfaces.len();
+                }
+            }
+            Focus::None => {}
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if self.view == View::Users {
+            if !self.user_summaries.is_empty() {
+                self.selected_user_idx = self.selected_user_idx
+                    .checked_sub(1)
+                    .unwrap_or(self.user_summaries.len() - 1);
+            }
+            return;
+        }
+        if self.view == View::Cgroups {
+            if !self.cgroups.is_empty() {
+                self.selected_cgroup_idx = self.selected_cgroup_idx
+                    .checked_sub(1)
+                    .unwrap_or(self.cgroups.len() - 1);
+            }
+            return;
+        }
+        if self.view == View::Processes {
+            if !self.process_view.is_empty() {
+                self.selected_process_idx = self.selected_process_idx
+                    .checked_sub(1)
+                    .unwrap_or(self.process_view.len() - 1);
+            }
+            return;
+        }
+        match self.focus {
+            Focus::Ports => self.select_prev_port(),
+            Focus::Network => {
+                if !self.network_interfaces.is_empty() {
+                    self.selected_interface_idx = self.selected_interface_idx
+                        .checked_sub(1)
+                        .unwrap_or(self.network_interfaces.len() - 1);
+                }
+            }
+            Focus::None => {}
+        }
+    }
+
+    pub fn selected_interface(&self) -> Option<&str> {
+        self.network_interfaces
+            .get(self.selected_interface_idx)
+            .map(|iface| iface.name.as_str())
+    }
+
+    /// Name of the interface whose detail popup is open, if any.
+    pub fn detail_interface(&self) -> Option<&str> {
+        match &self.detail_popup {
+            DetailPopup::Interface(details) => Some(details.name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// PID of the process whose detail popup is open, if any.
+    pub fn detail_process(&self) -> Option<u32> {
+        match &self.detail_popup {
+            DetailPopup::Process(details) => Some(details.pid),
+            _ => None,
+        }
+    }
+
+    pub fn open_popup(&mut self, popup: DetailPopup) {
+        self.detail_popup = popup;
+        self.detail_scroll = 0;
+    }
+
+    pub fn scroll_popup(&mut self, down: bool) {
+        self.detail_scroll = if down {
+            self.detail_scroll.saturating_add(1)
+        } else {
+            self.detail_scroll.saturating_sub(1)
+        };
+    }
+
+    pub fn close_popup(&mut self) {
+        self.detail_popup = DetailPopup::None;
+    }
+
+    pub fn is_popup_open(&self) -> bool {
+        !matches!(self.detail_popup, DetailPopup::None)
+    }
+
     pub fn select_next_port(&mut self) {
         if !self.open_ports.is_empty() {
             self.selected_port_idx = (self.selected_port_idx + 1) % self.open_ports.len();
@@ -102,18 +1081,65 @@ impl App {
     }
 
     pub fn request_kill_selected(&mut self) {
-        if let Some((port, name, _pid)) = self.open_ports.get(self.selected_port_idx) {
-            self.confirm_dialog = ConfirmDialog::KillProcess {
-                port: *port,
-                process_name: name.clone(),
-                selected_yes: false, // Default to "No" for safety
-            };
+        if let Some((port, name, pid)) = self.open_ports.get(self.selected_port_idx).cloned() {
+            self.open_dialog(pid, Some(port), name);
+        }
+    }
+
+    pub fn request_kill_selected_process(&mut self) {
+        if let Some(process) = self.selected_process() {
+            let (pid, name) = (process.pid, process.name.clone());
+            self.open_dialog(pid, None, name);
         }
     }
 
-    pub fn toggle_confirm_selection(&mut self) {
-        if let ConfirmDialog::KillProcess { selected_yes, .. } = &mut self.confirm_dialog {
-            *selected_yes = !*selected_yes;
+    /// Containers are stopped or restarted through Docker rather than killing their processes.
+    /// Service processes would just be restarted by systemd, so their units can be stopped or
+    /// restarted too; the plain kill stays first, as the `y` action.
+    fn open_dialog(&mut self, pid: u32, port: Option<u16>, process_name: String) {
+        let unit = self
+            .processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .and_then(|p| p.owner.as_ref()?.stoppable_unit())
+            .map(|(name, user)| (name.to_string(), user));
+
+        let (owner, actions) = if let Some(c) = self.container_of(pid, port) {
+            (
+                Some(format!("container {}", c.name)),
+                vec![
+                    Action::StopContainer { id: c.id.clone(), name: c.name.clone() },
+                    Action::RestartContainer { id: c.id.clone(), name: c.name.clone() },
+                ],
+            )
+        } else if let Some((name, user)) = unit {
+            (
+                Some(format!("unit {}", name)),
+                vec![
+                    Action::Kill(pid),
+                    Action::StopUnit { name: name.clone(), user },
+                    Action::RestartUnit { name, user },
+                ],
+            )
+        } else {
+            (None, vec![Action::Kill(pid)])
+        };
+
+        self.confirm_dialog = ConfirmDialog::Open {
+            pid,
+            port,
+            process_name,
+            owner,
+            actions,
+            selected: 0, // Default to "No" for safety
+        };
+    }
+
+    /// Move the highlighted dialog button forward or back, wrapping around.
+    pub fn cycle_confirm_selection(&mut self, forward: bool) {
+        if let ConfirmDialog::Open { actions, selected, .. } = &mut self.confirm_dialog {
+            let buttons = actions.len() + 1;
+            *selected = if forward { (*selected + 1) % buttons } else { (*selected + buttons - 1) % buttons };
         }
     }
 
@@ -121,22 +1147,144 @@ impl App {
         self.confirm_dialog = ConfirmDialog::None;
     }
 
-    pub fn confirm_dialog_action(&mut self) -> Option<u32> {
-        if let ConfirmDialog::KillProcess { selected_yes, .. } = &self.confirm_dialog {
-            if *selected_yes {
-                // Get the PID to kill
-                if let Some((_port, _name, pid)) = self.open_ports.get(self.selected_port_idx) {
-                    let pid = *pid;
-                    self.confirm_dialog = ConfirmDialog::None;
-                    return Some(pid);
-                }
+    pub fn confirm_dialog_action(&mut self) -> Option<Action> {
+        let dialog = std::mem::take(&mut self.confirm_dialog);
+        match dialog {
+            ConfirmDialog::Open { mut actions, selected, .. } if selected > 0 && selected <= actions.len() => {
+                Some(actions.swap_remove(selected - 1))
             }
+            _ => None,
         }
-        self.confirm_dialog = ConfirmDialog::None;
-        None
+    }
+
+    /// Confirm the dialog's first action regardless of the highlighted button (the `y` shortcut).
+    pub fn accept_dialog(&mut self) -> Option<Action> {
+        if let ConfirmDialog::Open { selected, .. } = &mut self.confirm_dialog {
+            *selected = 1;
+        }
+        self.confirm_dialog_action()
     }
 
     pub fn is_dialog_open(&self) -> bool {
         !matches!(self.confirm_dialog, ConfirmDialog::None)
     }
 }
+
+fn per_second(delta: u64, secs: f64) -> u64 {
+    (delta as f64 / secs).round() as u64
+}
+
+fn push_history<T>(history: &mut VecDeque<T>, value: T) {
+    if history.len() >= HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procfs::SocketCounts;
+
+    fn process(pid: u32, parent: Option<u32>, name: &str, memory: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent,
+            name: name.to_string(),
+            user: "root".to_string(),
+            uid: Some(0),
+            cpu: 1.0,
+            memory,
+            state: "Sleeping".to_string(),
+            threads: 1,
+            command: name.to_string(),
+            cwd: None,
+            owner: None,
+            label: name.to_string(),
+            sockets: SocketCounts::default(),
+        }
+    }
+
+    /// init with an ssh session running node below it, plus an orphan and a process that
+    /// names itself as its parent.
+    fn tree_app() -> App {
+        let mut app = App::new();
+        app.set_processes(vec![
+            process(12, Some(11), "node", 400),
+            process(1, None, "init", 10),
+            process(20, Some(1), "cron", 20),
+            process(10, Some(1), "sshd", 30),
+            process(11, Some(10), "bash", 40),
+            process(30, Some(999), "orphan", 50),
+            process(40, Some(40), "looped", 60),
+        ]);
+        app.set_process_sort(ProcessSort::Pid);
+        app.toggle_process_tree();
+        app
+    }
+
+    /// (pid, depth) of every visible row.
+    fn rows(app: &App) -> Vec<(u32, usize)> {
+        app.process_view.iter().zip(&app.tree_rows).map(|(&idx, row)| (app.processes[idx].pid, row.depth)).collect()
+    }
+
+    #[test]
+    fn lays_out_tree() {
+        let app = tree_app();
+        assert_eq!(rows(&app), [(1, 0), (10, 1), (11, 2), (12, 3), (20, 1), (30, 0), (40, 0)]);
+
+        let init = &app.tree_rows[0];
+        assert!(init.has_children);
+        assert_eq!((init.subtree_cpu, init.subtree_memory), (5.0, 500));
+        let node = &app.tree_rows[3];
+        assert!(!node.has_children);
+        assert_eq!(node.subtree_memory, 400);
+        // Missing and self-referencing parents make roots instead of vanishing
+        assert!(!app.tree_rows[5].has_children && !app.tree_rows[6].has_children);
+    }
+
+    #[test]
+    fn filter_keeps_ancestors_of_matches() {
+        let mut app = tree_app();
+        for c in "node".chars() {
+            app.push_filter_char(c);
+        }
+        assert_eq!(rows(&app), [(1, 0), (10, 1), (11, 2), (12, 3)]);
+
+        app.process_filter = "loop".to_string();
+        app.rebuild_process_view();
+        assert_eq!(rows(&app), [(40, 0)]);
+    }
+
+    #[test]
+    fn collapse_hides_descendants() {
+        let mut app = tree_app();
+        app.selected_process_idx = 1;
+        app.set_selected_collapsed(Some(true));
+        assert_eq!(rows(&app), [(1, 0), (10, 1), (20, 1), (30, 0), (40, 0)]);
+        assert!(app.tree_rows[1].collapsed);
+        // Totals still count what's hidden
+        assert_eq!(app.tree_rows[1].subtree_memory, 470);
+
+        // Jumping to a hidden process expands its ancestors
+        app.set_view(View::Dashboard);
+        app.jump_to_process(12);
+        assert_eq!(app.selected_process().map(|p| p.pid), Some(12));
+        assert_eq!(rows(&app).len(), 7);
+    }
+
+    #[test]
+    fn focuses_first_visible_panel() {
+        let mut app = App::new();
+        app.set_panels(vec![Panel::Cpu, Panel::Network]);
+        assert_eq!(app.focus, Focus::Network);
+        app.set_panels(vec![Panel::Cpu, Panel::Memory]);
+        assert_eq!(app.focus, Focus::None);
+        app.set_panels(vec![Panel::Ports, Panel::Network]);
+        assert_eq!(app.focus, Focus::Ports);
+        // A focused panel that stays visible keeps focus
+        app.toggle_focus();
+        app.set_panels(Vec::new());
+        assert_eq!(app.focus, Focus::Network);
+    }
+}
@@ -0,0 +1,348 @@
+//! Attribution of processes to containers, Kubernetes pods and systemd units from their cgroup.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Where the unified (v2) hierarchy is mounted on systemd hosts.
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// What a process belongs to, as far as its cgroup path tells.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Owner {
+    Container { runtime: String, id: String },
+    Pod { uid: String, container: Option<String> },
+    /// A systemd unit; `user` units belong to a per-user manager (`systemctl --user`)
+    Unit { name: String, user: bool },
+}
+
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Owner::Container { runtime, id } => write!(f, "{}:{}", runtime, short_id(id)),
+            Owner::Pod { uid, .. } => write!(f, "pod:{}", uid.get(..8).unwrap_or(uid)),
+            Owner::Unit { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Owner {
+    /// The systemd unit (and whether it's a `--user` one) that may be stopped or restarted in
+    /// place of killing the process. Only services qualify: a scope just groups processes
+    /// started elsewhere, like a terminal tab or tmux server, and can't be restarted. The
+    /// per-user manager and init are never offered.
+    pub fn stoppable_unit(&self) -> Option<(&str, bool)> {
+        match self {
+            Owner::Unit { name, user } if name.ends_with(".service") && !name.starts_with("user@") => {
+                Some((name, *user))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Container IDs are shown the way `docker ps` does, first 12 hex digits.
+pub fn short_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
+
+/// The cgroup path of `pid`: the unified (v2) hierarchy if present, else the systemd v1 hierarchy.
+pub fn cgroup_path(pid: u32) -> Option<String> {
+    let content = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+
+    // Format: hierarchy-ID:controller-list:cgroup-path
+    let mut fallback = None;
+    for line in content.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (Some(id), Some(controllers), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        if id == "0" && controllers.is_empty() {
+            return Some(path.to_string());
+        }
+        if controllers == "name=systemd" || fallback.is_none() {
+            fallback = Some(path.to_string());
+        }
+    }
+    fallback
+}
+
+pub fn owner_of(pid: u32) -> Option<Owner> {
+    cgroup_path(pid).and_then(|path| parse_owner(&path))
+}
+
+/// Work out the owner from a cgroup path. Handles both the systemd driver
+/// (`docker-<id>.scope`, `kubepods-pod<uid>.slice`) and the cgroupfs driver (`/docker/<id>`).
+pub fn parse_owner(path: &str) -> Option<Owner> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let mut pod_uid = None;
+    let mut container = None;
+    let mut unit: Option<String> = None;
+    let mut user_manager = false;
+
+    for (idx, segment) in segments.iter().enumerate() {
+        let name = segment
+            .strip_suffix(".scope")
+            .or_else(|| segment.strip_suffix(".slice"))
+            .unwrap_or(segment);
+
+        // Kubernetes pods: kubepods-burstable-pod<uid>.slice or /kubepods/burstable/pod<uid>
+        if segment.starts_with("kubepods") || segments.first() == Some(&"kubepods") {
+            if let Some(uid) = name.rsplit_once("-pod").map(|(_, uid)| uid).or_else(|| name.strip_prefix("pod")) {
+                pod_uid = Some(uid.replace('_', "-"));
+                continue;
+            }
+        }
+
+        // Container scopes: docker-<id>.scope, cri-containerd-<id>.scope, libpod-<id>.scope, crio-<id>.scope
+        if let Some((prefix, id)) = name.rsplit_once('-') {
+            if is_container_id(id) {
+                let runtime = match prefix {
+                    "docker" => "docker",
+                    "libpod" => "podman",
+                    "crio" => "cri-o",
+                    p if p.ends_with("containerd") => "containerd",
+                    _ => "container",
+                };
+                container = Some((runtime.to_string(), id.to_string()));
+                continue;
+            }
+        }
+
+        // cgroupfs driver: /docker/<id>, /machine/libpod/<id>, /kubepods/.../pod<uid>/<id>
+        if is_container_id(name) {
+            let runtime = match idx.checked_sub(1).map(|i| segments[i]) {
+                Some("docker") => "docker",
+                Some("libpod") => "podman",
+                _ => "container",
+            };
+            container = Some((runtime.to_string(), name.to_string()));
+            continue;
+        }
+
+        if segment.ends_with(".service") || (segment.ends_with(".scope") && !segment.starts_with("session-")) {
+            // Units below user@<uid>.service are managed by that user's systemd instance
+            if unit.as_deref().is_some_and(|u| u.starts_with("user@")) {
+                user_manager = true;
+            }
+            unit = Some(segment.to_string());
+        }
+    }
+
+    match (pod_uid, container, unit) {
+        (Some(uid), container, _) => Some(Owner::Pod { uid, container: container.map(|(_, id)| id) }),
+        (None, Some((runtime, id)), _) => Some(Owner::Container { runtime, id }),
+        (None, None, Some(name)) => Some(Owner::Unit { name, user: user_manager }),
+        (None, None, None) => None,
+    }
+}
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Raw counters of one cgroup. Totals include every descendant, as the kernel reports them.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CgroupStats {
+    /// Path below the cgroup root, "/" for the root itself
+    pub path: String,
+    pub depth: usize,
+    pub cpu_usage_usec: u64,
+    /// CPU quota in percent of one core, from cpu.max
+    pub cpu_max: Option<f64>,
+    pub memory_current: Option<u64>,
+    pub memory_max: Option<u64>,
+    pub io_read_bytes: u64,
+    pub io_write_bytes: u64,
+}
+
+/// Every cgroup under `root`, parents before children and siblings by name. Empty unless
+/// `root` is a cgroup v2 hierarchy.
+pub fn read_cgroups(root: &Path) -> Vec<CgroupStats> {
+    let mut cgroups = Vec::new();
+    if root.join("cgroup.controllers").exists() {
+        walk_cgroups(root, "/", 0, &mut cgroups);
+    }
+    cgroups
+}
+
+fn walk_cgroups(dir: &Path, path: &str, depth: usize, cgroups: &mut Vec<CgroupStats>) {
+    let read = |file: &str| std::fs::read_to_string(dir.join(file)).ok();
+
+    // Format: usage_usec N\nuser_usec N\n...
+    let cpu_usage_usec = read("cpu.stat")
+        .and_then(|stat| {
+            stat.lines()
+                .find_map(|line| line.strip_prefix("usage_usec "))
+                .and_then(|value| value.trim().parse().ok())
+        })
+        .unwrap_or(0);
+
+    // Format: "$MAX $PERIOD", where $MAX may be "max"
+    let cpu_max = read("cpu.max").and_then(|max| {
+        let mut fields = max.split_whitespace();
+        let quota: f64 = fields.next()?.parse().ok()?;
+        let period: f64 = fields.next()?.parse().ok()?;
+        (period > 0.0).then(|| quota / period * 100.0)
+    });
+
+    // Format: one "MAJ:MIN rbytes=N wbytes=N rios=N wios=N ..." line per device
+    let (mut io_read_bytes, mut io_write_bytes) = (0, 0);
+    for pair in read("io.stat").iter().flat_map(|stat| stat.split_whitespace()) {
+        if let Some(value) = pair.strip_prefix("rbytes=").and_then(|v| v.parse::<u64>().ok()) {
+            io_read_bytes += value;
+        } else if let Some(value) = pair.strip_prefix("wbytes=").and_then(|v| v.parse::<u64>().ok()) {
+            io_write_bytes += value;
+        }
+    }
+
+    cgroups.push(CgroupStats {
+        path: path.to_string(),
+        depth,
+        cpu_usage_usec,
+        cpu_max,
+        memory_current: read("memory.current").and_then(|v| v.trim().parse().ok()),
+        // "max" (no limit) fails to parse and becomes None
+        memory_max: read("memory.max").and_then(|v| v.trim().parse().ok()),
+        io_read_bytes,
+        io_write_bytes,
+    });
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    children.sort();
+
+    for name in children {
+        let child_path = if path == "/" { format!("/{}", name) } else { format!("{}/{}", path, name) };
+        walk_cgroups(&dir.join(&name), &child_path, depth + 1, cgroups);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "4f1c2a9d8e7b6a5c4f1c2a9d8e7b6a5c4f1c2a9d8e7b6a5c4f1c2a9d8e7b6a5c";
+
+    fn container(runtime: &str) -> Option<Owner> {
+        Some(Owner::Container { runtime: runtime.to_string(), id: ID.to_string() })
+    }
+
+    fn unit(name: &str, user: bool) -> Option<Owner> {
+        Some(Owner::Unit { name: name.to_string(), user })
+    }
+
+    #[test]
+    fn parses_owners() {
+        let pod = Some(Owner::Pod { uid: "8d2e-41b0".to_string(), container: Some(ID.to_string()) });
+        let user_slice = "/user.slice/user-1000.slice";
+        let cases = [
+            (format!("/system.slice/docker-{}.scope", ID), container("docker")),
+            (format!("/docker/{}", ID), container("docker")),
+            (format!("/machine.slice/libpod-{}.scope", ID), container("podman")),
+            (
+                format!(
+                    "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod8d2e_41b0.slice/cri-containerd-{}.scope",
+                    ID
+                ),
+                pod.clone(),
+            ),
+            (format!("/kubepods/burstable/pod8d2e-41b0/{}", ID), pod),
+            ("/system.slice/nginx.service".to_string(), unit("nginx.service", false)),
+            (format!("{}/user@1000.service/app.slice/vite.service", user_slice), unit("vite.service", true)),
+            (format!("{}/user@1000.service", user_slice), unit("user@1000.service", false)),
+            (
+                format!("{}/user@1000.service/app.slice/vte-spawn-5f0c.scope", user_slice),
+                unit("vte-spawn-5f0c.scope", true),
+            ),
+            (format!("{}/user@1000.service/tmux-spawn-9a1b.scope", user_slice), unit("tmux-spawn-9a1b.scope", true)),
+            (
+                format!("{}/user@1000.service/app.slice/app-code-4411.scope", user_slice),
+                unit("app-code-4411.scope", true),
+            ),
+            (format!("{}/session-4.scope", user_slice), None),
+            ("/init.scope".to_string(), unit("init.scope", false)),
+            ("/".to_string(), None),
+        ];
+        for (path, owner) in cases {
+            assert_eq!(parse_owner(&path), owner, "{}", path);
+        }
+    }
+
+    #[test]
+    fn offers_only_services_to_stop() {
+        let stoppable = |name: &str, user: bool| {
+            Owner::Unit { name: name.to_string(), user }.stoppable_unit().map(|(name, user)| (name.to_string(), user))
+        };
+        assert_eq!(stoppable("nginx.service", false), Some(("nginx.service".to_string(), false)));
+        assert_eq!(stoppable("vite.service", true), Some(("vite.service".to_string(), true)));
+        // Stopping these takes down far more than the selected process
+        assert_eq!(stoppable("vte-spawn-5f0c.scope", true), None);
+        assert_eq!(stoppable("tmux-spawn-9a1b.scope", true), None);
+        assert_eq!(stoppable("app-code-4411.scope", true), None);
+        assert_eq!(stoppable("user@1000.service", false), None);
+        assert_eq!(stoppable("init.scope", false), None);
+        assert_eq!(container("docker").unwrap().stoppable_unit(), None);
+    }
+
+    #[test]
+    fn reads_cgroup_tree() {
+        let root = std::env::temp_dir().join(format!("dashy-test-cgroups-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let write = |dir: &str, file: &str, content: &str| {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(file), content).unwrap();
+        };
+        write("", "cgroup.controllers", "cpu io memory pids\n");
+        write("", "cpu.stat", "usage_usec 9000000\nuser_usec 6000000\nsystem_usec 3000000\n");
+        write("system.slice", "cpu.stat", "usage_usec 5000\nuser_usec 4000\n");
+        write("system.slice", "cpu.max", "max 100000\n");
+        write("system.slice", "memory.current", "104857600\n");
+        write("system.slice", "memory.max", "max\n");
+        write("system.slice/nginx.service", "cpu.max", "50000 100000\n");
+        write("system.slice/nginx.service", "memory.max", "536870912\n");
+        write(
+            "system.slice/nginx.service",
+            "io.stat",
+            "8:0 rbytes=1000 wbytes=200 rios=10 wios=2 dbytes=0 dios=0\n259:0 rbytes=24 wbytes=56 rios=1 wios=1\n",
+        );
+        std::fs::create_dir_all(root.join("init.scope")).unwrap();
+
+        let cgroups = read_cgroups(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let paths: Vec<_> = cgroups.iter().map(|c| (c.path.as_str(), c.depth)).collect();
+        assert_eq!(paths, [("/", 0), ("/init.scope", 1), ("/system.slice", 1), ("/system.slice/nginx.service", 2)]);
+
+        assert_eq!(cgroups[0].cpu_usage_usec, 9_000_000);
+        let slice = &cgroups[2];
+        assert_eq!(slice.cpu_usage_usec, 5000);
+        assert_eq!(slice.cpu_max, None);
+        assert_eq!(slice.memory_current, Some(104_857_600));
+        assert_eq!(slice.memory_max, None);
+        let service = &cgroups[3];
+        assert_eq!(service.cpu_max, Some(50.0));
+        assert_eq!(service.memory_max, Some(536_870_912));
+        assert_eq!((service.io_read_bytes, service.io_write_bytes), (1024, 256));
+        // Files that aren't there read as zero or no limit
+        assert_eq!((cgroups[1].cpu_usage_usec, cgroups[1].memory_current), (0, None));
+    }
+
+    #[test]
+    fn ignores_non_cgroup2_root() {
+        let root = std::env::temp_dir().join(format!("dashy-test-no-cgroups-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("cpu")).unwrap();
+        let cgroups = read_cgroups(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(cgroups.is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+//! Command-line arguments.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+
+use crate::app::Panel;
+use crate::replay::{MAX_SPEED, MIN_SPEED};
+use crate::report::Metric;
+
+/// sysinfo needs this long between refreshes for meaningful CPU usage.
+const MIN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A fast, lightweight terminal system monitor.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Time between refreshes, e.g. `500ms`, `2s` or `1.5` (seconds)
+    #[arg(short, long, global = true, default_value = "1s", value_parser = parse_interval)]
+    pub interval: Duration,
+
+    /// Leave the mouse to the terminal, so text can be selected and copied
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Stop collecting while paused, leaving a gap in the graphs, instead of catching up on resume
+    #[arg(long)]
+    pub idle_when_paused: bool,
+
+    /// Dashboard panels to show, comma-separated [default: all]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub panels: Vec<Panel>,
+
+    /// Config file to use instead of ~/.config/dashy/config.toml
+    #[arg(short, long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub record: RecordArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Recording works with the dashboard, `stream` and `serve`.
+#[derive(Args)]
+#[command(next_help_heading = "Recording")]
+pub struct RecordArgs {
+    /// Append every sample to FILE, one JSON object per line
+    #[arg(long, global = true, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Rotate the recording once it reaches this size, e.g. `500MB`
+    #[arg(long, global = true, value_name = "SIZE", default_value = "100MB", value_parser = parse_size)]
+    pub record_max_size: u64,
+
+    /// Rotate the recording once it is this old, e.g. `6h`
+    #[arg(long, global = true, value_name = "AGE", default_value = "24h", value_parser = parse_duration)]
+    pub record_max_age: Duration,
+
+    /// Rotated recordings to keep as FILE.1 (newest) to FILE.N
+    #[arg(long, global = true, value_name = "N", default_value_t = 5)]
+    pub record_keep: usize,
+}
+
+/// Non-interactive modes. Without one dashy runs the dashboard.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Take two samples one interval apart and print them
+    Snapshot(SnapshotArgs),
+    /// Print one JSON object per interval (NDJSON) until interrupted
+    Stream(StreamArgs),
+    /// Serve Prometheus metrics over HTTP at /metrics
+    Serve(ServeArgs),
+    /// List listening ports and the processes holding them
+    Ports(PortsArgs),
+    /// Signal the processes listening on a port
+    KillPort(KillPortArgs),
+    /// Wait until a port is listening (or free), for scripts and CI
+    WaitPort(WaitPortArgs),
+    /// Play back a file written with --record in the dashboard
+    Replay(ReplayArgs),
+    /// Write metric history as CSV, from a recording or from samples taken now
+    Export(ExportArgs),
+}
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Json)]
+    pub format: Format,
+}
+
+#[derive(Args)]
+pub struct StreamArgs {
+    /// Metric groups to include, comma-separated [default: all]
+    #[arg(short, long, value_enum, value_delimiter = ',')]
+    pub metrics: Vec<Metric>,
+
+    /// Stop after this many lines
+    #[arg(short = 'n', long)]
+    pub count: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:9898")]
+    pub listen: SocketAddr,
+}
+
+#[derive(Args)]
+pub struct PortsArgs {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+}
+
+#[derive(Args)]
+pub struct KillPortArgs {
+    pub port: u16,
+
+    /// Signal to send, by name (TERM, KILL, INT, HUP, ...) or number
+    #[arg(short, long, default_value = "TERM", value_parser = parse_signal)]
+    pub signal: String,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("state").required(true).args(["listening", "free"])))]
+pub struct WaitPortArgs {
+    pub port: u16,
+
+    /// Wait until something listens on the port
+    #[arg(long)]
+    pub listening: bool,
+
+    /// Wait until nothing listens on the port
+    #[arg(long)]
+    pub free: bool,
+
+    /// Give up and exit non-zero after this long
+    #[arg(short, long, default_value = "30s", value_parser = parse_duration)]
+    pub timeout: Duration,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    pub file: PathBuf,
+
+    /// Playback speed relative to how it was recorded, from 0.25 to 64, e.g. `8` or `0.5`
+    #[arg(short, long, default_value_t = 1.0, value_parser = parse_speed)]
+    pub speed: f64,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Recording to convert (from --record); without one, take samples now
+    pub recording: Option<PathBuf>,
+
+    /// Samples to take when there's no recording, one per interval
+    #[arg(short = 'n', long, default_value_t = 60)]
+    pub count: usize,
+
+    /// Write to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Parse a duration with an `ms`, `s`, `m` or `h` suffix; a bare number means seconds.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, unit_secs) = if let Some(n) = text.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = text.strip_suffix('s') {
+        (n, 1.0)
+    } else if let Some(n) = text.strip_suffix('m') {
+        (n, 60.0)
+    } else if let Some(n) = text.strip_suffix('h') {
+        (n, 3600.0)
+    } else {
+        (text, 1.0)
+    };
+
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid duration `{}` (expected e.g. 500ms, 2s, 1m)", text))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("invalid duration `{}`", text));
+    }
+    Duration::try_from_secs_f64(value * unit_secs).map_err(|_| format!("duration `{}` is too long", text))
+}
+
+/// Parse a size in bytes with an optional `K`, `M` or `G` suffix (`KB`, `MB` and `GB` work too).
+fn parse_size(text: &str) -> Result<u64, String> {
+    let upper = text.trim().to_ascii_uppercase();
+    let number = upper.strip_suffix('B').unwrap_or(&upper);
+    let (number, multiplier) = if let Some(n) = number.strip_suffix('K') {
+        (n, 1u64 << 10)
+    } else if let Some(n) = number.strip_suffix('M') {
+        (n, 1 << 20)
+    } else if let Some(n) = number.strip_suffix('G') {
+        (n, 1 << 30)
+    } else {
+        (number, 1)
+    };
+
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size `{}` (expected e.g. 512K, 100MB, 2G)", text))?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(format!("invalid size `{}`", text));
+    }
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Accept `TERM`, `SIGTERM`, `term` or `15`, normalized to what `kill -s` takes.
+fn parse_signal(text: &str) -> Result<String, String> {
+    const SIGNALS: [&str; 9] = ["HUP", "INT", "QUIT", "KILL", "USR1", "USR2", "TERM", "STOP", "CONT"];
+
+    if text.parse::<u8>().is_ok() {
+        return Ok(text.to_string());
+    }
+    let name = text.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    if SIGNALS.contains(&name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!("unknown signal `{}` (expected one of {})", text, SIGNALS.join(", ")))
+    }
+}
+
+fn parse_speed(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(speed) if (MIN_SPEED..=MAX_SPEED).contains(&speed) => Ok(speed),
+        _ => Err(format!(
+            "invalid speed `{}` (expected a number from {} to {}, e.g. 2 or 0.5)",
+            text, MIN_SPEED, MAX_SPEED
+        )),
+    }
+}
+
+fn parse_interval(text: &str) -> Result<Duration, String> {
+    let interval = parse_duration(text)?;
+    if interval < MIN_INTERVAL {
+        return Err(format!("interval must be at least {}ms", MIN_INTERVAL.as_millis()));
+    }
+    Ok(interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5m"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("6h"), Ok(Duration::from_secs(6 * 3600)));
+        assert_eq!(parse_duration(" 30 s "), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("0.5"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("0"), Ok(Duration::ZERO));
+        for bad in ["", "s", "-1s", "NaN", "inf", "1d", "fast", "1e30h"] {
+            assert!(parse_duration(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512K"), Ok(512 << 10));
+        assert_eq!(parse_size("100MB"), Ok(100 << 20));
+        assert_eq!(parse_size("100mb"), Ok(100 << 20));
+        assert_eq!(parse_size("2G"), Ok(2 << 30));
+        assert_eq!(parse_size("0.5K"), Ok(512));
+        for bad in ["", "0", "-5M", "NaN", "MB", "10T"] {
+            assert!(parse_size(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn normalizes_signals() {
+        for text in ["SIGTERM", "TERM", "term", "sigterm"] {
+            assert_eq!(parse_signal(text), Ok("TERM".to_string()), "{}", text);
+        }
+        assert_eq!(parse_signal("15"), Ok("15".to_string()));
+        assert_eq!(parse_signal("kill"), Ok("KILL".to_string()));
+        for bad in ["", "SIG", "TERMINATE", "-9", "999"] {
+            assert!(parse_signal(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn parses_speeds() {
+        assert_eq!(parse_speed("8"), Ok(8.0));
+        assert_eq!(parse_speed("0.5"), Ok(0.5));
+        assert_eq!(parse_speed("0.25"), Ok(MIN_SPEED));
+        assert_eq!(parse_speed("64"), Ok(MAX_SPEED));
+        for bad in ["", "0", "0.2", "65", "1000", "-2", "NaN", "inf", "fast"] {
+            assert!(parse_speed(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn enforces_minimum_interval() {
+        assert_eq!(parse_interval("200ms"), Ok(MIN_INTERVAL));
+        assert_eq!(parse_interval("0.5"), Ok(Duration::from_millis(500)));
+        assert!(parse_interval("199ms").is_err());
+        assert!(parse_interval("0").is_err());
+        assert!(parse_interval("soon").is_err());
+    }
+}
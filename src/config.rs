@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub network: NetworkConfig,
+    pub docker: DockerConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Interfaces to show. Empty means every interface not excluded.
+    pub include: Vec<String>,
+    /// Interfaces to hide, applied after `include`.
+    pub exclude: Vec<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: vec!["lo".to_string(), "veth*".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DockerConfig {
+    pub enabled: bool,
+    /// Socket path. Defaults to `$DOCKER_HOST` (unix:// only) or /var/run/docker.sock.
+    pub socket: Option<String>,
+}
+
+impl Default for DockerConfig {
+    fn default() -> Self {
+        Self { enabled: true, socket: None }
+    }
+}
+
+impl NetworkConfig {
+    pub fn is_shown(&self, interface: &str) -> bool {
+        let included = self.include.is_empty()
+            || self.include.iter().any(|p| glob_match(p, interface));
+        included && !self.exclude.iter().any(|p| glob_match(p, interface))
+    }
+}
+
+impl Config {
+    /// Load `path`, or `~/.config/dashy/config.toml` falling back to defaults if it doesn't exist.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("invalid config in {}", path.display()))
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("dashy").join("config.toml"))
+}
+
+/// Match `name` against a pattern where `*` stands for any run of characters.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(remaining) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            if rest.is_empty() {
+                return true;
+            }
+            (0..=remaining.len())
+                .filter(|&i| remaining.is_char_boundary(i))
+                .any(|i| glob_match(rest, &remaining[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        let cases = [
+            ("eth0", "eth0", true),
+            ("eth0", "eth01", false),
+            ("veth*", "veth1a2b", true),
+            ("veth*", "veth", true),
+            ("veth*", "eth0", false),
+            ("*0", "eth0", true),
+            ("*0", "eth1", false),
+            ("br-*-int", "br-3f2a-int", true),
+            ("br-*-int", "br-3f2a-ext", false),
+            ("*", "anything", true),
+            ("*", "", true),
+            ("", "", true),
+            ("", "lo", false),
+            ("a*b*c", "axxbyyc", true),
+            ("a*b*c", "acb", false),
+            ("wl*", "wlän0", true),
+        ];
+        for (pattern, name, expected) in cases {
+            assert_eq!(glob_match(pattern, name), expected, "{} vs {}", pattern, name);
+        }
+    }
+
+    #[test]
+    fn applies_exclude_after_include() {
+        let filter = |include: &[&str], exclude: &[&str]| NetworkConfig {
+            include: include.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+        };
+
+        let defaults = NetworkConfig::default();
+        assert!(defaults.is_shown("eth0"));
+        assert!(defaults.is_shown("docker0"));
+        assert!(!defaults.is_shown("lo"));
+        assert!(!defaults.is_shown("veth9c1d"));
+
+        let only_ethernet = filter(&["eth*", "en*"], &[]);
+        assert!(only_ethernet.is_shown("enp3s0"));
+        assert!(!only_ethernet.is_shown("wlan0"));
+
+        // Excluded wins over included
+        let both = filter(&["eth*"], &["eth1"]);
+        assert!(both.is_shown("eth0"));
+        assert!(!both.is_shown("eth1"));
+
+        assert!(filter(&[], &[]).is_shown("lo"));
+    }
+}
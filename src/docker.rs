@@ -0,0 +1,271 @@
+//! Minimal Docker Engine API client over the local unix socket. Also works with Podman's
+//! Docker-compatible socket.
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+pub const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+#[derive(Clone)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub ports: Vec<PublishedPort>,
+}
+
+#[derive(Clone)]
+pub struct PublishedPort {
+    pub host_port: u16,
+    pub container_port: u16,
+    pub protocol: String,
+}
+
+/// Shape of an entry in `GET /containers/json`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiContainer {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    ports: Vec<ApiPort>,
+}
+
+#[derive(Deserialize)]
+struct ApiPort {
+    #[serde(rename = "PrivatePort")]
+    private_port: u16,
+    #[serde(rename = "PublicPort")]
+    public_port: Option<u16>,
+    #[serde(rename = "Type", default)]
+    protocol: String,
+}
+
+#[derive(Clone)]
+pub struct DockerClient {
+    socket: PathBuf,
+}
+
+impl DockerClient {
+    /// Connect to `socket`, or `$DOCKER_HOST` / the default socket. `None` if there's no socket.
+    pub fn connect(socket: Option<&str>) -> Option<Self> {
+        let socket = socket
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var("DOCKER_HOST")
+                    .ok()
+                    .and_then(|host| host.strip_prefix("unix://").map(PathBuf::from))
+            })
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET));
+        socket.exists().then_some(Self { socket })
+    }
+
+    pub fn list_containers(&self) -> Result<Vec<ContainerInfo>> {
+        let body = self.request("GET", "/containers/json", Duration::from_secs(2))?;
+        let containers: Vec<ApiContainer> =
+            serde_json::from_str(&body).context("unexpected response from Docker")?;
+
+        Ok(containers
+            .into_iter()
+            .map(|c| {
+                let mut ports: Vec<PublishedPort> = c
+                    .ports
+                    .into_iter()
+                    .filter_map(|p| {
+                        p.public_port.map(|host_port| PublishedPort {
+                            host_port,
+                            container_port: p.private_port,
+                            protocol: p.protocol,
+                        })
+                    })
+                    .collect();
+                // IPv4 and IPv6 bindings of the same port are listed separately
+                ports.sort_by_key(|p| (p.host_port, p.container_port));
+                ports.dedup_by_key(|p| (p.host_port, p.container_port));
+
+                ContainerInfo {
+                    name: c
+                        .names
+                        .first()
+                        .map(|n| n.trim_start_matches('/').to_string())
+                        .unwrap_or_else(|| c.id.chars().take(12).collect()),
+                    id: c.id,
+                    image: c.image,
+                    state: c.state,
+                    ports,
+                }
+            })
+            .collect())
+    }
+
+    pub fn stop(&self, id: &str) -> Result<()> {
+        self.request("POST", &format!("/containers/{}/stop", id), Duration::from_secs(30))
+            .map(|_| ())
+    }
+
+    pub fn restart(&self, id: &str) -> Result<()> {
+        self.request("POST", &format!("/containers/{}/restart", id), Duration::from_secs(30))
+            .map(|_| ())
+    }
+
+    /// Send one request and return the body of a 2xx response.
+    fn request(&self, method: &str, path: &str, timeout: Duration) -> Result<String> {
+        let mut stream = UnixStream::connect(&self.socket)
+            .with_context(|| format!("failed to connect to {}", self.socket.display()))?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        // HTTP/1.0 so the daemon closes the connection after the response
+        let request = format!("{} {} HTTP/1.0\r\nHost: docker\r\nContent-Length: 0\r\n\r\n", method, path);
+        stream.write_all(request.as_bytes())?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+
+        let header_end = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .context("malformed HTTP response from Docker")?;
+        let head = String::from_utf8_lossy(&response[..header_end]);
+        let body = &response[header_end + 4..];
+
+        let status: u16 = head
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse().ok())
+            .context("malformed HTTP response from Docker")?;
+
+        let chunked = head
+            .lines()
+            .any(|line| line.to_ascii_lowercase().starts_with("transfer-encoding: chunked"));
+        let body = if chunked { dechunk(body) } else { body.to_vec() };
+        let body = String::from_utf8_lossy(&body).to_string();
+
+        if !(200..300).contains(&status) {
+            // Errors come back as {"message": "..."}
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            bail!("Docker returned {}: {}", status, message.trim());
+        }
+        Ok(body)
+    }
+}
+
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    // Each chunk: <hex size>\r\n<data>\r\n, terminated by a zero-size chunk
+    while let Some(line_end) = body.windows(2).position(|w| w == b"\r\n") {
+        let size = std::str::from_utf8(&body[..line_end])
+            .ok()
+            .and_then(|s| usize::from_str_radix(s.trim(), 16).ok());
+        let data = &body[line_end + 2..];
+        match size {
+            Some(size) if size > 0 && data.len() >= size => {
+                out.extend_from_slice(&data[..size]);
+                body = data[size..].strip_prefix(b"\r\n").unwrap_or(&data[size..]);
+            }
+            _ => break,
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread::{self, JoinHandle};
+
+    /// A fake daemon on a fresh socket that answers one request with `response` and hands back
+    /// the request it got.
+    fn serve(name: &str, response: String) -> (DockerClient, JoinHandle<String>) {
+        let socket = std::env::temp_dir().join(format!("dashy-test-{}-{}.sock", std::process::id(), name));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (DockerClient { socket }, server)
+    }
+
+    #[test]
+    fn sends_request_and_returns_body() {
+        let (docker, server) = serve("plain", "HTTP/1.0 204 No Content\r\nServer: Docker\r\n\r\n".to_string());
+        let body = docker.request("POST", "/containers/abc/stop", Duration::from_secs(5)).unwrap();
+        assert_eq!(body, "");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /containers/abc/stop HTTP/1.0\r\n"), "{}", request);
+        let _ = std::fs::remove_file(&docker.socket);
+    }
+
+    #[test]
+    fn extracts_error_message() {
+        let (docker, server) = serve(
+            "error",
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\n\r\n{\"message\":\"No such container: abc\"}\n"
+                .to_string(),
+        );
+        let error = docker.request("POST", "/containers/abc/stop", Duration::from_secs(5)).unwrap_err();
+        assert_eq!(error.to_string(), "Docker returned 404: No such container: abc");
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&docker.socket);
+    }
+
+    #[test]
+    fn dechunks_body() {
+        assert_eq!(dechunk(b"5\r\nhello\r\n7\r\n world!\r\n0\r\n\r\n"), b"hello world!");
+        assert_eq!(dechunk(b"a\r\n0123456789\r\n0\r\n\r\n"), b"0123456789");
+        // Cut short: keep what arrived whole
+        assert_eq!(dechunk(b"5\r\nhello\r\n9\r\nwor"), b"hello");
+        assert_eq!(dechunk(b""), b"");
+    }
+
+    #[test]
+    fn lists_containers_with_deduplicated_ports() {
+        let parts = [
+            r#"[{"Id":"4f1c2a9d8e7b6a5c","Names":["/web"],"Image":"nginx","State":"running","Ports":["#,
+            r#"{"PrivatePort":80,"PublicPort":8080,"Type":"tcp"},{"PrivatePort":80,"PublicPort":8080,"Type":"tcp"},"#,
+            r#"{"PrivatePort":443,"Type":"tcp"}]},{"Id":"0123456789abcdef","Image":"redis","State":"exited"}]"#,
+        ];
+        let body: String = parts.iter().map(|part| format!("{:x}\r\n{}\r\n", part.len(), part)).collect();
+        let response = format!("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{}0\r\n\r\n", body);
+        let (docker, server) = serve("list", response);
+        let containers = docker.list_containers().unwrap();
+        assert!(server.join().unwrap().starts_with("GET /containers/json HTTP/1.0\r\n"));
+        let _ = std::fs::remove_file(&docker.socket);
+
+        assert_eq!(containers.len(), 2);
+        let web = &containers[0];
+        assert_eq!((web.name.as_str(), web.image.as_str(), web.state.as_str()), ("web", "nginx", "running"));
+        let ports: Vec<_> = web.ports.iter().map(|p| (p.host_port, p.container_port, p.protocol.as_str())).collect();
+        assert_eq!(ports, [(8080, 80, "tcp")]);
+        // No name: the short ID, as `docker ps` shows it
+        assert_eq!(containers[1].name, "0123456789ab");
+        assert!(containers[1].ports.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+//! CSV export of metric history for spreadsheets: one row per sample, one column per series,
+//! units in the headers.
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use crate::app::App;
+use crate::system::Sample;
+use crate::ui::format_timestamp;
+
+/// The per-core, per-disk and per-interface columns, which depend on the machine.
+struct Columns {
+    cores: usize,
+    disks: Vec<String>,
+    interfaces: Vec<String>,
+}
+
+impl Columns {
+    fn of_history(app: &App) -> Self {
+        Self {
+            cores: app.cpu_core_history.iter().map(Vec::len).max().unwrap_or(0),
+            disks: names(app.disk_device_history.iter().flatten().map(|(name, _, _)| name)),
+            interfaces: names(app.interface_history.iter().flatten().map(|(name, _, _)| name)),
+        }
+    }
+
+    fn of_samples(samples: &[Sample]) -> Self {
+        Self {
+            cores: samples.iter().map(|s| s.cpu_cores.len()).max().unwrap_or(0),
+            disks: names(samples.iter().flat_map(|s| &s.disk_devices).map(|d| &d.name)),
+            interfaces: names(samples.iter().flat_map(|s| &s.network).map(|i| &i.name)),
+        }
+    }
+}
+
+fn names<'a>(names: impl Iterator<Item = &'a String>) -> Vec<String> {
+    names.cloned().collect::<BTreeSet<_>>().into_iter().collect()
+}
+
+/// Write everything `App` still has in its history graphs, except a first sample that only set
+/// the baseline for rates. Returns the number of rows.
+pub fn write_history(out: &mut impl Write, app: &App) -> io::Result<usize> {
+    let columns = Columns::of_history(app);
+    write_header(out, &columns)?;
+    let rows = app.rated_history_start()..app.history_times.len();
+    for index in rows.clone() {
+        write_row(out, app, index, &columns)?;
+    }
+    Ok(rows.len())
+}
+
+/// Run samples through `App` and write a row for each. The first only sets the baseline for
+/// rates, so it gets no row. Returns the number of rows.
+pub fn write_samples(out: &mut impl Write, samples: Vec<Sample>) -> io::Result<usize> {
+    let columns = Columns::of_samples(&samples);
+    write_header(out, &columns)?;
+
+    let mut app = App::new();
+    let mut rows = 0;
+    for (n, sample) in samples.into_iter().enumerate() {
+        app.update(sample);
+        if n > 0 {
+            write_row(out, &app, app.history_times.len() - 1, &columns)?;
+            rows += 1;
+        }
+    }
+    Ok(rows)
+}
+
+fn write_header(out: &mut impl Write, columns: &Columns) -> io::Result<()> {
+    let mut header = vec!["time (UTC)".to_string(), "cpu (%)".to_string()];
+    header.extend((0..columns.cores).map(|core| format!("cpu{} (%)", core)));
+    header.extend(["memory used (bytes)", "disk read (bytes/s)", "disk write (bytes/s)"].map(String::from));
+    for disk in &columns.disks {
+        header.push(format!("{} read (bytes/s)", disk));
+        header.push(format!("{} write (bytes/s)", disk));
+    }
+    header.extend(["network rx (bytes/s)", "network tx (bytes/s)"].map(String::from));
+    for interface in &columns.interfaces {
+        header.push(format!("{} rx (bytes/s)", interface));
+        header.push(format!("{} tx (bytes/s)", interface));
+    }
+    write_record(out, &header)
+}
+
+/// One history entry. Disks and interfaces that weren't there at the time get empty cells.
+fn write_row(out: &mut impl Write, app: &App, index: usize, columns: &Columns) -> io::Result<()> {
+    let value = |v: Option<&u64>| v.map(u64::to_string).unwrap_or_default();
+    let cores = app.cpu_core_history.get(index);
+    let disks = app.disk_device_history.get(index);
+    let interfaces = app.interface_history.get(index);
+
+    let mut row = vec![
+        app.history_times
+            .get(index)
+            .map(|&ms| format!("{}.{:03}", format_timestamp(ms), ms % 1000))
+            .unwrap_or_default(),
+        app.cpu_history.get(index).map(|cpu| format!("{:.1}", cpu)).unwrap_or_default(),
+    ];
+    row.extend((0..columns.cores).map(|core| {
+        cores
+            .and_then(|cores| cores.get(core))
+            .map(|usage| format!("{:.1}", usage))
+            .unwrap_or_default()
+    }));
+    row.push(value(app.memory_history.get(index)));
+    row.push(value(app.disk_read_history.get(index)));
+    row.push(value(app.disk_write_history.get(index)));
+    for disk in &columns.disks {
+        let rates = disks.and_then(|disks| disks.iter().find(|(name, _, _)| name == disk));
+        row.push(value(rates.map(|(_, read, _)| read)));
+        row.push(value(rates.map(|(_, _, write)| write)));
+    }
+    row.push(value(app.network_rx_history.get(index)));
+    row.push(value(app.network_tx_history.get(index)));
+    for interface in &columns.interfaces {
+        let rates = interfaces.and_then(|interfaces| interfaces.iter().find(|(name, _, _)| name == interface));
+        row.push(value(rates.map(|(_, rx, _)| rx)));
+        row.push(value(rates.map(|(_, _, tx)| tx)));
+    }
+    write_record(out, &row)
+}
+
+fn write_record(out: &mut impl Write, fields: &[String]) -> io::Result<()> {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    writeln!(out, "{}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::HISTORY_LEN;
+    use crate::system::DiskIo;
+
+    /// One sample a second, with the disk read counter going up 1000 bytes each.
+    fn samples(count: u64) -> Vec<Sample> {
+        (0..count)
+            .map(|n| {
+                let disk = DiskIo { name: "sda".to_string(), read_bytes: 5000 + n * 1000, write_bytes: 0 };
+                Sample {
+                    disk: (5000 + n * 1000, 0),
+                    disk_devices: vec![disk],
+                    ..Sample::at(1_700_000_000_000 + n * 1000)
+                }
+            })
+            .collect()
+    }
+
+    fn csv(write: impl FnOnce(&mut Vec<u8>) -> io::Result<usize>) -> (usize, Vec<String>) {
+        let mut out = Vec::new();
+        let rows = write(&mut out).unwrap();
+        (rows, String::from_utf8(out).unwrap().lines().map(String::from).collect())
+    }
+
+    fn history(samples: Vec<Sample>) -> (usize, Vec<String>) {
+        let mut app = App::new();
+        for sample in samples {
+            app.update(sample);
+        }
+        csv(|out| write_history(out, &app))
+    }
+
+    #[test]
+    fn skips_baseline_like_write_samples() {
+        let (rows, lines) = history(samples(5));
+        assert_eq!((rows, lines.len()), (4, 5));
+        assert_eq!(lines, csv(|out| write_samples(out, samples(5))).1);
+
+        assert_eq!(
+            lines[0],
+            "time (UTC),cpu (%),memory used (bytes),disk read (bytes/s),disk write (bytes/s),\
+             sda read (bytes/s),sda write (bytes/s),network rx (bytes/s),network tx (bytes/s)"
+        );
+        assert_eq!(lines[1], "2023-11-14 22:13:21.000,0.0,0,1000,0,1000,0,0,0");
+    }
+
+    #[test]
+    fn keeps_oldest_entry_once_baseline_scrolled_off() {
+        let count = HISTORY_LEN as u64 + 5;
+        let (rows, lines) = history(samples(count));
+        assert_eq!(rows, HISTORY_LEN);
+        // The history is the tail of what a full export writes
+        let (_, all) = csv(|out| write_samples(out, samples(count)));
+        assert_eq!(lines[1..], all[all.len() - HISTORY_LEN..]);
+    }
+
+    #[test]
+    fn quotes_fields() {
+        let (_, lines) = csv(|out| {
+            write_record(out, &["plain".to_string(), "a,b".to_string(), "say \"hi\"".to_string()])?;
+            Ok(1)
+        });
+        assert_eq!(lines, ["plain,\"a,b\",\"say \"\"hi\"\"\""]);
+    }
+}
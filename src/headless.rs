@@ -0,0 +1,268 @@
+//! Non-interactive modes: they run the same collectors and `App` as the dashboard, but print
+//! instead of drawing.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::app::App;
+use crate::cli::{ExportArgs, Format, KillPortArgs, PortsArgs, RecordArgs, SnapshotArgs, StreamArgs, WaitPortArgs};
+use crate::config::Config;
+use crate::export;
+use crate::record::{self, Recorder};
+use crate::report::{self, Metric, PortReport, Report};
+use crate::system::{self, Collector};
+use crate::ui::format_bytes;
+
+/// Rates need two samples, so sample, wait one interval, sample again and print.
+pub fn snapshot(args: &SnapshotArgs, interval: Duration, config: &Config) -> Result<()> {
+    let mut collector = Collector::new();
+    let mut app = App::new();
+
+    app.update(collector.collect(config));
+    std::thread::sleep(interval);
+    app.update(collector.collect(config));
+
+    let report = report::build(&app, &[]);
+    let mut stdout = io::stdout().lock();
+    let written = match args.format {
+        Format::Json => write_json(&mut stdout, &report),
+        Format::Text => write_text_report(&mut stdout, &report),
+    };
+    ignore_broken_pipe(written.and_then(|()| stdout.flush()))?;
+    Ok(())
+}
+
+fn write_json(out: &mut impl Write, report: &Report) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, report)?;
+    writeln!(out)
+}
+
+/// The reader went away (`| head`), which is a normal way to stop, so that's `None` rather than
+/// an error.
+fn ignore_broken_pipe<T>(result: io::Result<T>) -> Result<Option<T>> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(None),
+        result => Ok(Some(result?)),
+    }
+}
+
+fn write_text_report(out: &mut impl Write, report: &Report) -> io::Result<()> {
+    if let Some(cpu) = &report.cpu {
+        writeln!(out, "CPU      {:.1}%", cpu.usage_percent)?;
+    }
+    if let Some(memory) = &report.memory {
+        writeln!(out, "Memory   {} / {}", format_bytes(memory.used_bytes), format_bytes(memory.total_bytes))?;
+    }
+    if let Some(disk) = &report.disk {
+        writeln!(
+            out,
+            "Disk     read {}/s, write {}/s",
+            format_bytes(disk.read_bytes_per_sec),
+            format_bytes(disk.write_bytes_per_sec)
+        )?;
+    }
+    if let Some(network) = &report.network {
+        writeln!(
+            out,
+            "Network  rx {}/s, tx {}/s",
+            format_bytes(network.rx_bytes_per_sec),
+            format_bytes(network.tx_bytes_per_sec)
+        )?;
+        for iface in &network.interfaces {
+            writeln!(
+                out,
+                "  {:<14} rx {}/s, tx {}/s",
+                iface.name,
+                format_bytes(iface.rx_bytes_per_sec),
+                format_bytes(iface.tx_bytes_per_sec)
+            )?;
+        }
+    }
+    if let Some(ports) = &report.ports {
+        writeln!(out)?;
+        write_port_table(out, ports)?;
+    }
+    Ok(())
+}
+
+fn write_port_table(out: &mut impl Write, ports: &[PortReport]) -> io::Result<()> {
+    writeln!(out, "{:<7} {:<8} {:<28} {:<22} COMMAND", "PORT", "PID", "PROCESS", "OWNER")?;
+    for port in ports {
+        writeln!(
+            out,
+            "{:<7} {:<8} {:<28} {:<22} {}",
+            port.port,
+            port.pid,
+            port.label,
+            port.owner.as_deref().unwrap_or("-"),
+            port.command
+        )?;
+    }
+    Ok(())
+}
+
+pub fn ports(args: &PortsArgs, config: &Config) -> Result<()> {
+    let mut app = App::new();
+    app.update(Collector::new().collect(config));
+
+    let report = report::build(&app, &[Metric::Ports]);
+    let mut stdout = io::stdout().lock();
+    let written = match args.format {
+        Format::Json => write_json(&mut stdout, &report),
+        Format::Text => write_port_table(&mut stdout, report.ports.as_deref().unwrap_or_default()),
+    };
+    ignore_broken_pipe(written.and_then(|()| stdout.flush()))?;
+    Ok(())
+}
+
+/// Signals that end a process unless it handles them, by name and number.
+const TERMINATING_SIGNALS: [&str; 8] = ["INT", "QUIT", "KILL", "TERM", "2", "3", "9", "15"];
+/// How long processes get to let go of the port after a terminating signal.
+const KILL_GRACE: Duration = Duration::from_secs(3);
+
+/// Signal every process listening on the port. Fails if nothing is, or if something still is
+/// after a terminating signal, so scripts can tell.
+pub fn kill_port(args: &KillPortArgs) -> Result<()> {
+    let collector = Collector::new();
+    let owners: Vec<(u32, String)> = system::get_port_listeners(&collector.sys)
+        .into_iter()
+        .filter(|(port, _, _)| *port == args.port)
+        .map(|(_, name, pid)| (pid, name))
+        .collect();
+
+    if owners.is_empty() {
+        if system::is_port_listening(args.port) {
+            bail!("port {} is held by a process dashy can't see; try again as root", args.port);
+        }
+        bail!("nothing is listening on port {}", args.port);
+    }
+
+    if !args.yes {
+        if !io::stdin().is_terminal() {
+            bail!("refusing to signal without confirmation; pass --yes");
+        }
+        for (pid, name) in &owners {
+            eprintln!("  {} (PID {})", name, pid);
+        }
+        eprint!("Send SIG{} to the processes listening on port {}? [y/N] ", args.signal, args.port);
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            bail!("aborted");
+        }
+    }
+
+    let mut failed = false;
+    for (pid, name) in &owners {
+        if system::signal_process(*pid, &args.signal) {
+            println!("Sent SIG{} to {} (PID {})", args.signal, name, pid);
+        } else {
+            eprintln!("Failed to signal {} (PID {})", name, pid);
+            failed = true;
+        }
+    }
+    if failed {
+        bail!("could not signal every process on port {}", args.port);
+    }
+
+    // A listener left over ignored the signal, or belongs to a process dashy can't see
+    if TERMINATING_SIGNALS.contains(&args.signal.as_str()) {
+        let deadline = Instant::now() + KILL_GRACE;
+        while system::is_port_listening(args.port) {
+            if Instant::now() >= deadline {
+                bail!("port {} is still in use after {:?}", args.port, KILL_GRACE);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+    Ok(())
+}
+
+/// Check the port every interval until it is in the wanted state or time runs out. Whose
+/// listener it is doesn't matter, so this works for other users' processes too.
+pub fn wait_port(args: &WaitPortArgs, interval: Duration) -> Result<()> {
+    let deadline = Instant::now() + args.timeout;
+    loop {
+        if system::is_port_listening(args.port) == args.listening {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            let state = if args.listening { "listening" } else { "free" };
+            bail!("timed out after {:?} waiting for port {} to be {}", args.timeout, args.port, state);
+        }
+        std::thread::sleep(interval.min(deadline - now));
+    }
+}
+
+/// CSV of a recording, or of `count` samples taken one interval apart.
+pub fn export(args: &ExportArgs, interval: Duration, config: &Config) -> Result<()> {
+    let samples = match &args.recording {
+        Some(path) => record::read(path)?.1,
+        None => {
+            let mut collector = Collector::new();
+            // One extra sample as the baseline for rates
+            let mut samples = vec![collector.collect(config)];
+            for _ in 0..args.count {
+                std::thread::sleep(interval);
+                samples.push(collector.collect(config));
+            }
+            samples
+        }
+    };
+
+    match &args.output {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+            let mut out = BufWriter::new(file);
+            let rows = export::write_samples(&mut out, samples)?;
+            out.flush()?;
+            eprintln!("Wrote {} rows to {}", rows, path.display());
+        }
+        None => {
+            let mut stdout = io::stdout().lock();
+            let written = export::write_samples(&mut stdout, samples).and_then(|_| stdout.flush());
+            ignore_broken_pipe(written)?;
+        }
+    }
+    Ok(())
+}
+
+/// One report per interval, each on its own line and flushed so consumers see it immediately.
+pub fn stream(args: &StreamArgs, interval: Duration, record: &RecordArgs, config: &Config) -> Result<()> {
+    let mut recorder = Recorder::from_args(record)?;
+    let mut collector = Collector::new();
+    let mut app = App::new();
+    // The first sample only sets the baseline for rates, but it belongs in the recording
+    let sample = collector.collect(config);
+    if let Some(recorder) = &mut recorder {
+        recorder.write(&sample).context("failed to write the recording")?;
+    }
+    app.update(sample);
+    let mut last_tick = Instant::now();
+
+    let mut stdout = io::stdout().lock();
+    let mut written = 0;
+    while args.count.is_none_or(|count| written < count) {
+        // Collecting takes a while, so sleep only for what's left of the interval
+        std::thread::sleep(interval.saturating_sub(last_tick.elapsed()));
+        last_tick = Instant::now();
+        let sample = collector.collect(config);
+        if let Some(recorder) = &mut recorder {
+            recorder.write(&sample).context("failed to write the recording")?;
+        }
+        app.update(sample);
+
+        let mut line = serde_json::to_vec(&report::build(&app, &args.metrics))?;
+        line.push(b'\n');
+        if ignore_broken_pipe(stdout.write_all(&line).and_then(|()| stdout.flush()))?.is_none() {
+            return Ok(());
+        }
+        written += 1;
+    }
+    Ok(())
+}
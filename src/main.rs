@@ -1,48 +1,108 @@
 mod app;
+mod cgroup;
+mod cli;
+mod config;
+mod docker;
+mod export;
+mod headless;
+mod procfs;
+mod prometheus;
+mod record;
+mod replay;
+mod report;
 mod system;
+mod systemd;
 mod ui;
 
-use std::io;
-use std::time::{Duration, Instant};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::sync::mpsc;
+use std::time::Instant;
 
 use anyhow::Result;
+use clap::Parser;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use sysinfo::{Disks, Networks, System};
 
-use app::{App, ConfirmDialog};
+use app::{Action, App, DetailPopup, Focus, Panel, ProcessSort, View};
+use cli::{Cli, Command};
+use config::Config;
+use docker::{ContainerInfo, DockerClient};
+use record::Recorder;
+use replay::Replay;
+use system::Collector;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?;
+
+    match &cli.command {
+        Some(Command::Snapshot(args)) => headless::snapshot(args, cli.interval, &config),
+        Some(Command::Stream(args)) => headless::stream(args, cli.interval, &cli.record, &config),
+        Some(Command::Serve(args)) => prometheus::serve(args.listen, cli.interval, &cli.record, config).await,
+        Some(Command::Ports(args)) => headless::ports(args, &config),
+        Some(Command::KillPort(args)) => headless::kill_port(args),
+        Some(Command::WaitPort(args)) => headless::wait_port(args, cli.interval),
+        Some(Command::Export(args)) => headless::export(args, cli.interval, &config),
+        Some(Command::Replay(args)) => {
+            let replay = Replay::load(&args.file, args.speed)?;
+            run_tui(cli, &config, Some(replay))
+        }
+        None => run_tui(cli, &config, None),
+    }
+}
+
+/// The dashboard, showing this machine or, with `replay`, a recording. A replay only looks:
+/// killing, stopping and inspecting act on live processes, so they're off.
+fn run_tui(cli: Cli, config: &Config, mut replay: Option<Replay>) -> Result<()> {
+    let live = replay.is_none();
+    // Fail before taking over the terminal if the recording can't be opened
+    let mut recorder = if live { Recorder::from_args(&cli.record)? } else { None };
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if !cli.no_mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and system instances
     let mut app = App::new();
-    let mut sys = System::new_all();
-    let mut disks = Disks::new_with_refreshed_list();
-    let mut networks = Networks::new_with_refreshed_list();
-
-    // Initial refresh
-    sys.refresh_all();
+    app.set_panels(cli.panels);
+    let mut collector = Collector::new();
+    let docker = if live && config.docker.enabled {
+        DockerClient::connect(config.docker.socket.as_deref())
+    } else {
+        None
+    };
+    // Container actions and Docker polls run on their own thread and report back here
+    let (update_tx, update_rx) = mpsc::channel::<Update>();
+    let mut polling_docker = false;
 
-    let tick_rate = Duration::from_secs(1);
+    let tick_rate = cli.interval;
     let mut last_tick = Instant::now();
+    if let Some(replay) = &mut replay {
+        replay.seek(&mut app, 0);
+        app.replay = Some(replay.status());
+    }
 
     loop {
         // Draw UI
         terminal.draw(|f| ui::draw(f, &app))?;
 
         // Handle events
-        let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+        let timeout = match &replay {
+            Some(replay) => replay.time_until_next(),
+            None => tick_rate.saturating_sub(last_tick.elapsed()),
+        };
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
                 // Handle Ctrl+C always
@@ -51,16 +111,21 @@ async fn main() -> Result<()> {
                 } else if app.is_dialog_open() {
                     // Dialog mode key handling
                     match key.code {
-                        KeyCode::Tab | KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
-                            app.toggle_confirm_selection();
+                        KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                            app.cycle_confirm_selection(true);
+                        }
+                        KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                            app.cycle_confirm_selection(false);
                         }
                         KeyCode::Enter => {
-                            if let Some(pid) = app.confirm_dialog_action() {
-                                system::kill_process(pid);
-                                // Force refresh after kill
-                                sys.refresh_all();
-                                let ports = system::get_open_ports(&sys);
-                                app.open_ports = ports;
+                            if let Some(action) = app.confirm_dialog_action() {
+                                app.status_message = run_action(action, docker.as_ref(), &update_tx);
+                                // Force refresh after the action, unless the screen is frozen
+                                if !app.paused {
+                                    collector.sys.refresh_all();
+                                    app.set_processes(system::get_processes(&collector.sys, &collector.users));
+                                    app.set_ports(system::get_open_ports(&collector.sys));
+                                }
                             }
                         }
                         KeyCode::Esc | KeyCode::Char('n') => {
@@ -68,36 +133,144 @@ async fn main() -> Result<()> {
                         }
                         KeyCode::Char('y') => {
                             // Quick confirm with 'y'
-                            if let ConfirmDialog::KillProcess { .. } = &app.confirm_dialog {
-                                if let Some((_port, _name, pid)) = app.open_ports.get(app.selected_port_idx) {
-                                    let pid = *pid;
-                                    app.cancel_dialog();
-                                    system::kill_process(pid);
-                                    sys.refresh_all();
-                                    let ports = system::get_open_ports(&sys);
-                                    app.open_ports = ports;
+                            if let Some(action) = app.accept_dialog() {
+                                app.status_message = run_action(action, docker.as_ref(), &update_tx);
+                                if !app.paused {
+                                    collector.sys.refresh_all();
+                                    app.set_processes(system::get_processes(&collector.sys, &collector.users));
+                                    app.set_ports(system::get_open_ports(&collector.sys));
                                 }
                             }
                         }
                         _ => {}
                     }
+                } else if app.is_popup_open() {
+                    // Popup mode key handling
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                            app.close_popup();
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            app.scroll_popup(true);
+                        }
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            app.scroll_popup(false);
+                        }
+                        _ => {}
+                    }
+                } else if app.filter_editing {
+                    // Process filter input
+                    match key.code {
+                        KeyCode::Enter => app.finish_filter(false),
+                        KeyCode::Esc => app.finish_filter(true),
+                        KeyCode::Backspace => app.pop_filter_char(),
+                        KeyCode::Char(c) => app.push_filter_char(c),
+                        _ => {}
+                    }
+                } else if replay.as_mut().is_some_and(|replay| replay.handle_key(&mut app, key.code)) {
+                    // Playback keys
                 } else {
                     // Normal mode key handling
                     match key.code {
                         KeyCode::Char('q') | KeyCode::Char('Q') => {
                             app.should_quit = true;
                         }
+                        KeyCode::Esc
+                            if app.view == View::Processes
+                                && (!app.process_filter.is_empty() || app.user_filter.is_some()) =>
+                        {
+                            app.clear_filters();
+                        }
                         KeyCode::Esc => {
                             app.should_quit = true;
                         }
+                        KeyCode::Char('1') => {
+                            app.set_view(View::Dashboard);
+                        }
+                        KeyCode::Char('2') => {
+                            app.set_view(View::Processes);
+                        }
+                        KeyCode::Char('3') => {
+                            app.set_view(View::Cgroups);
+                        }
+                        KeyCode::Char('4') => {
+                            app.set_view(View::Users);
+                        }
+                        KeyCode::Tab if app.view == View::Dashboard => {
+                            app.toggle_focus();
+                        }
+                        KeyCode::Char('w') if app.view == View::Dashboard => {
+                            app.toggle_ports_wide();
+                        }
+                        KeyCode::Char('g') => {
+                            app.toggle_group_by_owner();
+                        }
+                        KeyCode::Char('p') => {
+                            app.toggle_pause();
+                        }
+                        KeyCode::Char('e') => {
+                            app.status_message = Some(export_history(&app));
+                        }
+                        KeyCode::Char('/') if app.view == View::Processes => {
+                            app.start_filter();
+                        }
+                        KeyCode::Char('s') if app.view == View::Processes => {
+                            app.cycle_process_sort();
+                        }
+                        KeyCode::Char('c') if app.view == View::Processes => {
+                            app.set_process_sort(ProcessSort::Sockets);
+                        }
+                        KeyCode::Char('r') if app.view == View::Processes => {
+                            app.reverse_process_sort();
+                        }
+                        KeyCode::Char('t') if app.view == View::Processes => {
+                            app.toggle_process_tree();
+                        }
+                        KeyCode::Char('t')
+                            if app.view == View::Dashboard && app.shows(Panel::Ports) && app.focus == Focus::Ports =>
+                        {
+                            if let Some(pid) = app.selected_port_pid() {
+                                app.jump_to_process(pid);
+                            }
+                        }
+                        KeyCode::Char(' ') if app.view == View::Processes => {
+                            app.set_selected_collapsed(None);
+                        }
+                        KeyCode::Left | KeyCode::Char('h') if app.view == View::Processes => {
+                            app.set_selected_collapsed(Some(true));
+                        }
+                        KeyCode::Right | KeyCode::Char('l') if app.view == View::Processes => {
+                            app.set_selected_collapsed(Some(false));
+                        }
                         KeyCode::Down | KeyCode::Char('j') => {
-                            app.select_next_port();
+                            app.select_next();
                         }
                         KeyCode::Up | KeyCode::Char('k') => {
-                            app.select_prev_port();
+                            app.select_prev();
                         }
-                        KeyCode::Enter => {
-                            app.request_kill_selected();
+                        KeyCode::Enter => match (app.view, app.focus) {
+                            (View::Processes, _) if live => app.request_kill_selected_process(),
+                            (View::Dashboard, Focus::Ports) if live => app.request_kill_selected(),
+                            (View::Dashboard, Focus::Network) if live => {
+                                if let Some(name) = app.selected_interface() {
+                                    let details = system::get_interface_details(&collector.networks, name);
+                                    app.open_popup(DetailPopup::Interface(details));
+                                }
+                            }
+                            (View::Users, _) => app.drill_into_user(),
+                            _ => {}
+                        },
+                        KeyCode::Char('i') if live => {
+                            let pid = match (app.view, app.focus) {
+                                (View::Processes, _) => app.selected_process().map(|p| p.pid),
+                                (View::Dashboard, Focus::Ports) => app.selected_port_pid(),
+                                (View::Dashboard, Focus::Network | Focus::None) | (View::Cgroups, _) | (View::Users, _) => None,
+                            };
+                            let details =
+                                pid.and_then(|pid| system::get_process_details(&collector.sys, &collector.users, pid));
+                            if let Some(details) = details {
+                                app.open_popup(DetailPopup::Process(Box::new(details)));
+                            }
                         }
                         _ => {}
                     }
@@ -109,19 +282,60 @@ async fn main() -> Result<()> {
             break;
         }
 
-        // Update data every tick
-        if last_tick.elapsed() >= tick_rate {
-            sys.refresh_all();
-            disks.refresh(true);
-            networks.refresh(true);
+        while let Ok(update) = update_rx.try_recv() {
+            match update {
+                Update::Status(message) => app.status_message = Some(message),
+                Update::Containers(containers) => {
+                    polling_docker = false;
+                    if !app.paused {
+                        app.set_containers(containers);
+                    }
+                }
+            }
+        }
 
-            let cpu = system::get_cpu_usage(&sys);
-            let memory = system::get_memory(&sys);
-            let disk = system::get_disk_io(&disks);
-            let network = system::get_network_io(&networks);
-            let ports = system::get_open_ports(&sys);
+        if let Some(replay) = &mut replay {
+            replay.tick(&mut app);
+            app.replay = Some(replay.status());
+        } else if last_tick.elapsed() >= tick_rate {
+            // Update data every tick. Paused, samples are held back (or not taken at all)
+            // and the popups keep what they show too.
+            if !(app.paused && cli.idle_when_paused) {
+                let sample = collector.collect(config);
+                if let Some(Err(e)) = recorder.as_mut().map(|recorder| recorder.write(&sample)) {
+                    app.status_message = Some(format!("Recording stopped: {}", e));
+                    recorder = None;
+                }
+                if app.paused {
+                    app.hold(sample);
+                } else {
+                    app.update(sample);
+                }
+            }
 
-            app.update(cpu, memory, disk, network, ports);
+            if !app.paused {
+                // The daemon can take up to its timeout to answer, so ask off the UI thread, one
+                // request at a time
+                if let Some(docker) = docker.clone().filter(|_| !polling_docker) {
+                    polling_docker = true;
+                    let updates = update_tx.clone();
+                    std::thread::spawn(move || {
+                        // A stopped daemon just means no container names until it's back
+                        let _ = updates.send(Update::Containers(docker.list_containers().unwrap_or_default()));
+                    });
+                }
+
+                // Keep an open popup current (link state can flip, sockets come and go)
+                if let Some(name) = app.detail_interface() {
+                    let details = system::get_interface_details(&collector.networks, name);
+                    app.detail_popup = DetailPopup::Interface(details);
+                } else if let Some(pid) = app.detail_process() {
+                    match system::get_process_details(&collector.sys, &collector.users, pid) {
+                        Some(details) => app.detail_popup = DetailPopup::Process(Box::new(details)),
+                        None => app.close_popup(),
+                    }
+                }
+            }
 
             last_tick = Instant::now();
         }
@@ -129,12 +343,95 @@ async fn main() -> Result<()> {
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    if !cli.no_mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     terminal.show_cursor()?;
 
     Ok(())
 }
+
+/// Write the history graphs to a timestamped CSV in the working directory.
+fn export_history(app: &App) -> String {
+    if app.rated_history_start() >= app.history_times.len() {
+        return "Nothing to export yet".to_string();
+    }
+    let stamp = app.sample_time_ms().map(ui::format_timestamp).unwrap_or_default();
+    let name = format!("dashy-{}", stamp.replace(['-', ':'], "").replace(' ', "-"));
+    let result = create_new_csv(&name).and_then(|(path, file)| {
+        let mut out = io::BufWriter::new(file);
+        let rows = export::write_history(&mut out, app)?;
+        out.flush()?;
+        Ok((path, rows))
+    });
+    match result {
+        Ok((path, rows)) => format!("Exported {} samples to {}", rows, path),
+        Err(e) => format!("Export failed: {}", e),
+    }
+}
+
+/// Create `NAME.csv`, or `NAME-2.csv` and so on if it's taken, so exports of the same second
+/// (a paused replay, say) don't overwrite each other.
+fn create_new_csv(name: &str) -> io::Result<(String, File)> {
+    let mut path = format!("{}.csv", name);
+    for n in 2.. {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => path = format!("{}-{}.csv", name, n),
+            result => return result.map(|file| (path, file)),
+        }
+    }
+    unreachable!("ran out of file names")
+}
+
+/// What background threads send back to the event loop.
+enum Update {
+    /// Outcome of a container or unit action
+    Status(String),
+    Containers(Vec<ContainerInfo>),
+}
+
+/// Carry out a confirmed dialog action, returning a status line to show. Stopping a container
+/// or unit waits for it to exit, so those run on a thread and send their outcome to `status`.
+fn run_action(action: Action, docker: Option<&DockerClient>, status: &mpsc::Sender<Update>) -> Option<String> {
+    let restart = matches!(action, Action::RestartContainer { .. } | Action::RestartUnit { .. });
+    match action {
+        Action::Kill(pid) => (!system::kill_process(pid)).then(|| format!("Failed to kill PID {}", pid)),
+        Action::StopContainer { id, name } | Action::RestartContainer { id, name } => {
+            let Some(docker) = docker.cloned() else {
+                return Some("Docker is not available".to_string());
+            };
+            in_background(status, format!("container {}", name), restart, move || {
+                if restart { docker.restart(&id) } else { docker.stop(&id) }
+            })
+        }
+        Action::StopUnit { name, user } | Action::RestartUnit { name, user } => {
+            in_background(status, format!("unit {}", name), restart, move || {
+                if restart { systemd::restart(&name, user) } else { systemd::stop(&name, user) }
+            })
+        }
+    }
+}
+
+fn in_background(
+    status: &mpsc::Sender<Update>,
+    target: String,
+    restart: bool,
+    job: impl FnOnce() -> Result<()> + Send + 'static,
+) -> Option<String> {
+    let (verb, progress, done) = if restart {
+        ("restart", "Restarting", "Restarted")
+    } else {
+        ("stop", "Stopping", "Stopped")
+    };
+    let message = format!("{} {}…", progress, target);
+    let status = status.clone();
+    std::thread::spawn(move || {
+        let message = match job() {
+            Ok(()) => format!("{} {}", done, target),
+            Err(e) => format!("Failed to {} {}: {:#}", verb, target, e),
+        };
+        let _ = status.send(Update::Status(message));
+    });
+    Some(message)
+}
@@ -0,0 +1,259 @@
+//! Linux `/proc` readers for data sysinfo doesn't expose. Every function returns an empty
+//! result when the file is missing or unreadable (other platforms, other users' processes).
+
+use std::collections::{HashMap, HashSet};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone)]
+pub struct SocketInfo {
+    pub protocol: &'static str,
+    pub local: String,
+    pub remote: String,
+    pub state: &'static str,
+}
+
+/// TCP/UDP sockets held by one process, by state.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SocketCounts {
+    pub total: usize,
+    /// (state, count), most common first
+    pub by_state: Vec<(String, usize)>,
+}
+
+impl SocketCounts {
+    pub fn tally<'a>(sockets: impl Iterator<Item = &'a SocketInfo>) -> Self {
+        let mut by_state: HashMap<&'static str, usize> = HashMap::new();
+        for socket in sockets {
+            *by_state.entry(socket.state).or_default() += 1;
+        }
+        let mut by_state: Vec<(String, usize)> = by_state
+            .into_iter()
+            .map(|(state, count)| (state.to_string(), count))
+            .collect();
+        by_state.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Self {
+            total: by_state.iter().map(|(_, count)| count).sum(),
+            by_state,
+        }
+    }
+}
+
+/// Count the sockets of `pid` found in `sockets` (from `read_sockets`).
+pub fn count_sockets(pid: u32, sockets: &HashMap<u64, SocketInfo>) -> SocketCounts {
+    if sockets.is_empty() {
+        return SocketCounts::default();
+    }
+    SocketCounts::tally(socket_inodes(pid).iter().filter_map(|inode| sockets.get(inode)))
+}
+
+/// Inodes of the sockets held open by `pid`, from the `socket:[inode]` links in /proc/<pid>/fd.
+pub fn socket_inodes(pid: u32) -> HashSet<u64> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return HashSet::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy();
+            target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|inode| inode.parse().ok())
+        })
+        .collect()
+}
+
+/// Every TCP/UDP socket on the host, keyed by inode.
+pub fn read_sockets() -> HashMap<u64, SocketInfo> {
+    let mut sockets = HashMap::new();
+
+    for (protocol, path) in [
+        ("tcp", "/proc/net/tcp"),
+        ("tcp6", "/proc/net/tcp6"),
+        ("udp", "/proc/net/udp"),
+        ("udp6", "/proc/net/udp6"),
+    ] {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        // Format: sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode ...
+        for line in content.lines().skip(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 10 {
+                continue;
+            }
+            let Ok(inode) = parts[9].parse::<u64>() else {
+                continue;
+            };
+            // Inode 0 means the socket is no longer attached to a process (e.g. TIME_WAIT)
+            if inode == 0 {
+                continue;
+            }
+            sockets.insert(
+                inode,
+                SocketInfo {
+                    protocol,
+                    local: decode_address(parts[1]),
+                    remote: decode_address(parts[2]),
+                    state: socket_state(protocol, parts[3]),
+                },
+            );
+        }
+    }
+
+    sockets
+}
+
+/// Ports with a listening TCP socket (v4 or v6), whichever process holds it.
+pub fn listening_ports() -> HashSet<u16> {
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|content| parse_listening_ports(&content))
+        .collect()
+}
+
+fn parse_listening_ports(content: &str) -> Vec<u16> {
+    // Same format as in read_sockets; state 0A is LISTEN
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.get(3) != Some(&"0A") {
+                return None;
+            }
+            let (_, port) = parts[1].rsplit_once(':')?;
+            u16::from_str_radix(port, 16).ok()
+        })
+        .collect()
+}
+
+/// Decode a `/proc/net` hex `ADDR:PORT`, where the address is stored as native-endian 32-bit words.
+fn decode_address(field: &str) -> String {
+    let Some((addr, port)) = field.split_once(':') else {
+        return field.to_string();
+    };
+    let port = u16::from_str_radix(port, 16).unwrap_or(0);
+
+    let words: Vec<u32> = (0..addr.len() / 8)
+        .filter_map(|i| addr.get(i * 8..i * 8 + 8))
+        .filter_map(|word| u32::from_str_radix(word, 16).ok())
+        .collect();
+
+    // 8 hex digits for IPv4, 32 for IPv6; anything else isn't an address we know
+    match words.as_slice() {
+        _ if words.len() * 8 != addr.len() => field.to_string(),
+        [v4] => format!("{}:{}", Ipv4Addr::from(v4.to_ne_bytes()), port),
+        [a, b, c, d] => {
+            let mut octets = [0u8; 16];
+            for (chunk, word) in octets.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            format!("[{}]:{}", Ipv6Addr::from(octets), port)
+        }
+        _ => field.to_string(),
+    }
+}
+
+fn socket_state(protocol: &str, code: &str) -> &'static str {
+    match (protocol.starts_with("udp"), code) {
+        (true, "01") => "ESTABLISHED",
+        (true, _) => "UNCONN",
+        (false, "01") => "ESTABLISHED",
+        (false, "02") => "SYN_SENT",
+        (false, "03") => "SYN_RECV",
+        (false, "04") => "FIN_WAIT1",
+        (false, "05") => "FIN_WAIT2",
+        (false, "06") => "TIME_WAIT",
+        (false, "07") => "CLOSE",
+        (false, "08") => "CLOSE_WAIT",
+        (false, "09") => "LAST_ACK",
+        (false, "0A") => "LISTEN",
+        (false, "0B") => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Resource limits from /proc/<pid>/limits as (name, soft, hard) with units appended.
+pub fn limits(pid: u32) -> Vec<(String, String, String)> {
+    let Ok(content) = std::fs::read_to_string(format!("/proc/{}/limits", pid)) else {
+        return Vec::new();
+    };
+
+    // Columns are space-padded and names contain single spaces, so split on runs of 2+
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line
+                .split("  ")
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .collect();
+            if columns.len() < 3 {
+                return None;
+            }
+            let units = columns.get(3).copied().unwrap_or("");
+            let with_units = |value: &str| {
+                if units.is_empty() || value == "unlimited" {
+                    value.to_string()
+                } else {
+                    format!("{} {}", value, units)
+                }
+            };
+            Some((columns[0].to_string(), with_units(columns[1]), with_units(columns[2])))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // /proc/net stores addresses in host byte order; these are as a little-endian kernel writes them
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn decodes_addresses() {
+        assert_eq!(decode_address("0100007F:0BB8"), "127.0.0.1:3000");
+        assert_eq!(decode_address("00000000:0016"), "0.0.0.0:22");
+        assert_eq!(decode_address("0A01A8C0:C350"), "192.168.1.10:50000");
+        assert_eq!(decode_address("00000000000000000000000000000000:1F90"), "[::]:8080");
+        assert_eq!(decode_address("00000000000000000000000001000000:0277"), "[::1]:631");
+        assert_eq!(decode_address("0000000000000000FFFF00000100007F:1538"), "[::ffff:127.0.0.1]:5432");
+        assert_eq!(decode_address("B80D0120000000000000000001000000:01BB"), "[2001:db8::1]:443");
+    }
+
+    #[test]
+    fn keeps_undecodable_addresses() {
+        assert_eq!(decode_address("garbage"), "garbage");
+        assert_eq!(decode_address("0100007F0000:0050"), "0100007F0000:0050");
+    }
+
+    #[test]
+    fn finds_listening_ports() {
+        let tcp6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 31337 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000001000000:0BB8 00000000000000000000000001000000:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 40112 1 0000000000000000 20 4 30 10 -1
+   2: 00000000000000000000000001000000:D2F0 00000000000000000000000001000000:0BB8 06 00000000:00000000 03:00000ebc 00000000     0        0 0 3 0000000000000000
+";
+        // Owned by root (uid 0) or not: only the state matters
+        assert_eq!(parse_listening_ports(tcp6), [8080]);
+        assert!(parse_listening_ports("").is_empty());
+    }
+
+    #[test]
+    fn names_socket_states() {
+        assert_eq!(socket_state("tcp", "0A"), "LISTEN");
+        assert_eq!(socket_state("tcp6", "01"), "ESTABLISHED");
+        assert_eq!(socket_state("tcp", "06"), "TIME_WAIT");
+        assert_eq!(socket_state("tcp", "0C"), "UNKNOWN");
+        assert_eq!(socket_state("udp", "07"), "UNCONN");
+        assert_eq!(socket_state("udp6", "01"), "ESTABLISHED");
+    }
+}
@@ -0,0 +1,304 @@
+//! Prometheus exporter: the dashboard's collectors behind a `/metrics` endpoint. Counters are
+//! exported raw (Prometheus computes rates itself), gauges as last sampled.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+
+use crate::cli::RecordArgs;
+use crate::config::Config;
+use crate::record::Recorder;
+use crate::system::{Collector, InterfaceIo, Sample};
+
+/// How long a client gets to send its request headers before the connection is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Collect every `interval` and serve the latest sample until interrupted.
+pub async fn serve(listen: SocketAddr, interval: Duration, record: &RecordArgs, config: Config) -> Result<()> {
+    let mut recorder = Recorder::from_args(record)?;
+    let mut collector = Collector::new();
+    let sample = collector.collect(&config);
+    if let Some(recorder) = &mut recorder {
+        recorder.write(&sample).context("failed to write the recording")?;
+    }
+    let latest = Arc::new(RwLock::new(render(&sample)));
+
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("failed to listen on {}", listen))?;
+    eprintln!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+
+    let metrics = Arc::clone(&latest);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            // sysinfo and the /proc readers are blocking
+            let text = tokio::task::block_in_place(|| {
+                let sample = collector.collect(&config);
+                if let Some(Err(e)) = recorder.as_mut().map(|recorder| recorder.write(&sample)) {
+                    eprintln!("Recording stopped: {}", e);
+                    recorder = None;
+                }
+                render(&sample)
+            });
+            *metrics.write().await = text;
+        }
+    });
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let metrics = Arc::clone(&latest);
+        tokio::spawn(async move {
+            // A client hanging up mid-request is nothing to report
+            let _ = handle(stream, metrics).await;
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream, metrics: Arc<RwLock<String>>) -> Result<()> {
+    // Only the request line matters; read until the end of the headers
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    tokio::time::timeout(REQUEST_TIMEOUT, async {
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        anyhow::Ok(())
+    })
+    .await
+    .context("timed out reading the request")??;
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let (method, target) = (request_line.next().unwrap_or(""), request_line.next().unwrap_or(""));
+    // Scrape configs may add `params`, which nothing here uses
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", "/metrics") => ("200 OK", "text/plain; version=0.0.4", metrics.read().await.clone()),
+        ("GET" | "HEAD", "/") => ("200 OK", "text/plain", "dashy exporter, metrics at /metrics\n".to_string()),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes()).await?;
+    }
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Metric name, help text and how to read the counter from an interface.
+type InterfaceCounter = (&'static str, &'static str, fn(&InterfaceIo) -> u64);
+
+/// Format a sample in the Prometheus text exposition format.
+pub fn render(sample: &Sample) -> String {
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+        let _ = writeln!(out, "# HELP dashy_{} {}", name, help);
+        let _ = writeln!(out, "# TYPE dashy_{} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "dashy_{}{} {}", name, labels, value);
+        }
+    };
+
+    family("cpu_usage_percent", "gauge", "CPU usage across all cores.", vec![(String::new(), sample.cpu.to_string())]);
+    family(
+        "cpu_core_usage_percent",
+        "gauge",
+        "CPU usage per logical core.",
+        sample
+            .cpu_cores
+            .iter()
+            .enumerate()
+            .map(|(core, usage)| (labels(&[("core", &core.to_string())]), usage.to_string()))
+            .collect(),
+    );
+    family("memory_used_bytes", "gauge", "Memory in use.", vec![(String::new(), sample.memory.0.to_string())]);
+    family("memory_total_bytes", "gauge", "Installed memory.", vec![(String::new(), sample.memory.1.to_string())]);
+    family("disk_read_bytes_total", "counter", "Bytes read from disks.", vec![(String::new(), sample.disk.0.to_string())]);
+    family(
+        "disk_written_bytes_total",
+        "counter",
+        "Bytes written to disks.",
+        vec![(String::new(), sample.disk.1.to_string())],
+    );
+
+    let interface_counters: [InterfaceCounter; 8] = [
+        ("network_receive_bytes_total", "Bytes received.", |i| i.rx_bytes),
+        ("network_transmit_bytes_total", "Bytes transmitted.", |i| i.tx_bytes),
+        ("network_receive_packets_total", "Packets received.", |i| i.rx_packets),
+        ("network_transmit_packets_total", "Packets transmitted.", |i| i.tx_packets),
+        ("network_receive_errors_total", "Receive errors.", |i| i.rx_errors),
+        ("network_transmit_errors_total", "Transmit errors.", |i| i.tx_errors),
+        ("network_receive_drops_total", "Received packets dropped.", |i| i.rx_drops),
+        ("network_transmit_drops_total", "Transmitted packets dropped.", |i| i.tx_drops),
+    ];
+    for (name, help, value) in interface_counters {
+        family(
+            name,
+            "counter",
+            help,
+            sample
+                .network
+                .iter()
+                .map(|iface| (labels(&[("interface", &iface.name)]), value(iface).to_string()))
+                .collect(),
+        );
+    }
+
+    let p = &sample.protocols;
+    for (name, help, value) in [
+        ("tcp_retransmits_total", "TCP segments retransmitted.", p.tcp_retransmits),
+        ("tcp_active_opens_total", "Outgoing TCP connections opened.", p.tcp_active_opens),
+        ("tcp_passive_opens_total", "Incoming TCP connections accepted.", p.tcp_passive_opens),
+        ("tcp_estab_resets_total", "Established TCP connections reset.", p.tcp_estab_resets),
+        ("tcp_out_resets_total", "TCP resets sent.", p.tcp_out_resets),
+        ("tcp_listen_overflows_total", "TCP accept queue overflows.", p.tcp_listen_overflows),
+        ("tcp_listen_drops_total", "TCP connection attempts dropped while listening.", p.tcp_listen_drops),
+        ("udp_in_errors_total", "UDP datagrams received with errors.", p.udp_in_errors),
+        ("udp_rcvbuf_errors_total", "UDP datagrams dropped for a full receive buffer.", p.udp_rcvbuf_errors),
+    ] {
+        family(name, "counter", help, vec![(String::new(), value.to_string())]);
+    }
+
+    family(
+        "listening_port",
+        "gauge",
+        "A listening port and the process holding it; always 1.",
+        sample
+            .ports
+            .iter()
+            .map(|(port, process, pid)| {
+                let labels = labels(&[("port", &port.to_string()), ("pid", &pid.to_string()), ("process", process)]);
+                (labels, "1".to_string())
+            })
+            .collect(),
+    );
+
+    out
+}
+
+/// `{name="value",...}` with values escaped as the exposition format requires.
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::ProtocolCounters;
+
+    fn sample() -> Sample {
+        Sample {
+            cpu: 12.5,
+            cpu_cores: vec![20.0, 5.0],
+            memory: (1024, 4096),
+            disk: (100, 200),
+            network: vec![InterfaceIo {
+                name: "eth0".to_string(),
+                rx_bytes: 3000,
+                tx_bytes: 4000,
+                rx_packets: 30,
+                tx_packets: 40,
+                rx_errors: 1,
+                tx_errors: 2,
+                rx_drops: 3,
+                tx_drops: 4,
+            }],
+            protocols: ProtocolCounters { tcp_retransmits: 7, ..Default::default() },
+            ports: vec![(8080, "node".to_string(), 4242)],
+            ..Sample::at(1_700_000_000_000)
+        }
+    }
+
+    #[test]
+    fn renders_exposition_format() {
+        let text = render(&sample());
+        for expected in [
+            "# HELP dashy_cpu_usage_percent CPU usage across all cores.\n\
+             # TYPE dashy_cpu_usage_percent gauge\n\
+             dashy_cpu_usage_percent 12.5\n",
+            "dashy_cpu_core_usage_percent{core=\"0\"} 20\ndashy_cpu_core_usage_percent{core=\"1\"} 5\n",
+            "dashy_memory_used_bytes 1024\n",
+            "# TYPE dashy_disk_written_bytes_total counter\ndashy_disk_written_bytes_total 200\n",
+            "dashy_network_receive_bytes_total{interface=\"eth0\"} 3000\n",
+            "dashy_network_transmit_drops_total{interface=\"eth0\"} 4\n",
+            "dashy_tcp_retransmits_total 7\n",
+            "dashy_listening_port{port=\"8080\",pid=\"4242\",process=\"node\"} 1\n",
+        ] {
+            assert!(text.contains(expected), "missing {:?} in\n{}", expected, text);
+        }
+        // Every sample line belongs to a declared family
+        for line in text.lines().filter(|line| !line.starts_with('#')) {
+            let name = line.split(['{', ' ']).next().unwrap();
+            assert!(text.contains(&format!("# TYPE {} ", name)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(labels(&[]), "{}");
+        assert_eq!(labels(&[("port", "80"), ("pid", "1")]), r#"{port="80",pid="1"}"#);
+        assert_eq!(labels(&[("process", "say \"hi\"")]), r#"{process="say \"hi\""}"#);
+        assert_eq!(labels(&[("process", r"C:\bin")]), r#"{process="C:\\bin"}"#);
+        assert_eq!(labels(&[("process", "two\nlines")]), r#"{process="two\nlines"}"#);
+    }
+
+    #[tokio::test]
+    async fn serves_metrics() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let metrics = Arc::new(RwLock::new("dashy_up 1\n".to_string()));
+        let server = tokio::spawn(async move {
+            for _ in 0..4 {
+                let (stream, _) = listener.accept().await.unwrap();
+                handle(stream, Arc::clone(&metrics)).await.unwrap();
+            }
+        });
+
+        let send = |method: &'static str, path: &'static str| async move {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            let request = format!("{} {} HTTP/1.1\r\nHost: localhost\r\n\r\n", method, path);
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        let response = send("GET", "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.ends_with("\r\n\r\ndashy_up 1\n"), "{}", response);
+        let response = send("GET", "/metrics?collect=all").await;
+        assert!(response.ends_with("\r\n\r\ndashy_up 1\n"), "{}", response);
+        // Same headers, but no body
+        let response = send("HEAD", "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("Content-Length: 11\r\n"), "{}", response);
+        assert!(response.ends_with("\r\n\r\n"), "{}", response);
+        assert!(send("GET", "/nope").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,320 @@
+//! Recordings: every sample appended to a file as one line of JSON, for `dashy replay` and for
+//! digging through after an incident. Files are rotated by size and age, like logs.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+use crate::cgroup::CgroupStats;
+use crate::cli::RecordArgs;
+use crate::system::{DiskIo, InterfaceIo, ProcessInfo, ProtocolCounters, Sample};
+
+/// Bumped whenever a sample field is renamed or removed.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Processes kept per sample, once by CPU and once by memory. Listening ones are always kept.
+const TOP_PROCESSES: usize = 25;
+
+/// First line of every recording file.
+#[derive(Serialize, Deserialize)]
+pub struct Header {
+    pub dashy_recording: u32,
+    pub host: Option<String>,
+    /// Unix time in milliseconds when the file was started, for age rotation after a restart.
+    /// Missing from files written before it was added.
+    #[serde(default)]
+    pub started_ms: Option<u64>,
+}
+
+/// A `Sample` as written: the same fields, so a line reads back as a `Sample`, but only the
+/// processes worth looking at later.
+#[derive(Serialize)]
+struct Frame<'a> {
+    timestamp_ms: u64,
+    cpu: f64,
+    cpu_cores: &'a [f32],
+    memory: (u64, u64),
+    disk: (u64, u64),
+    disk_devices: &'a [DiskIo],
+    network: &'a [InterfaceIo],
+    protocols: ProtocolCounters,
+    processes: Vec<&'a ProcessInfo>,
+    ports: &'a [(u16, String, u32)],
+    cgroups: &'a [CgroupStats],
+}
+
+impl<'a> Frame<'a> {
+    fn new(sample: &'a Sample) -> Self {
+        Self {
+            timestamp_ms: sample.timestamp_ms,
+            cpu: sample.cpu,
+            cpu_cores: &sample.cpu_cores,
+            memory: sample.memory,
+            disk: sample.disk,
+            disk_devices: &sample.disk_devices,
+            network: &sample.network,
+            protocols: sample.protocols,
+            processes: top_processes(sample),
+            ports: &sample.ports,
+            cgroups: &sample.cgroups,
+        }
+    }
+}
+
+fn top_processes(sample: &Sample) -> Vec<&ProcessInfo> {
+    let mut keep: HashSet<u32> = sample.ports.iter().map(|(_, _, pid)| *pid).collect();
+
+    let mut by_usage: Vec<&ProcessInfo> = sample.processes.iter().collect();
+    by_usage.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+    keep.extend(by_usage.iter().take(TOP_PROCESSES).map(|p| p.pid));
+    by_usage.sort_by_key(|p| Reverse(p.memory));
+    keep.extend(by_usage.iter().take(TOP_PROCESSES).map(|p| p.pid));
+
+    sample.processes.iter().filter(|p| keep.contains(&p.pid)).collect()
+}
+
+/// Appends samples to a file, moving it to `FILE.1` (and older ones to `FILE.2`, ...) once it
+/// grows past the size limit or gets too old.
+pub struct Recorder {
+    path: PathBuf,
+    max_size: u64,
+    max_age: Duration,
+    keep: usize,
+    file: File,
+    size: u64,
+    created: SystemTime,
+}
+
+impl Recorder {
+    /// Start recording if `--record` was given. An existing file is appended to.
+    pub fn from_args(args: &RecordArgs) -> Result<Option<Self>> {
+        let Some(path) = &args.record else {
+            return Ok(None);
+        };
+        let mut recorder = Self {
+            path: path.clone(),
+            max_size: args.record_max_size,
+            max_age: args.record_max_age,
+            keep: args.record_keep,
+            file: open(path).with_context(|| format!("failed to open {}", path.display()))?,
+            size: 0,
+            created: SystemTime::now(),
+        };
+        recorder.start_file().with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(Some(recorder))
+    }
+
+    /// Append one sample, rotating first if the current file is full or old enough.
+    pub fn write(&mut self, sample: &Sample) -> io::Result<()> {
+        let age = SystemTime::now().duration_since(self.created).unwrap_or_default();
+        if self.size >= self.max_size || age >= self.max_age {
+            self.rotate()?;
+        }
+        self.write_line(&Frame::new(sample))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for n in (1..self.keep).rev() {
+            match fs::rename(rotated(&self.path, n), rotated(&self.path, n + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+        self.file = open(&self.path)?;
+        self.start_file()
+    }
+
+    /// Pick up where an existing file left off, or give a new one its header.
+    fn start_file(&mut self) -> io::Result<()> {
+        self.size = self.file.metadata()?.len();
+        if self.size > 0 {
+            self.created = started_at(&self.path)?;
+            return Ok(());
+        }
+        self.created = SystemTime::now();
+        let started_ms = self.created.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        self.write_line(&Header {
+            dashy_recording: FORMAT_VERSION,
+            host: System::host_name(),
+            started_ms: Some(started_ms),
+        })
+    }
+
+    /// Lines go out in a single write each, so a crash leaves at most one partial line behind.
+    fn write_line(&mut self, value: &impl Serialize) -> io::Result<()> {
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Read a recording's host and samples. Rotated files can be `cat`ed together first; headers in
+/// between and a line cut short by a crash are skipped.
+pub fn read(path: &Path) -> Result<(Option<String>, Vec<Sample>)> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut host = None;
+    let mut samples = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+        if let Ok(header) = serde_json::from_str::<Header>(&line) {
+            if header.dashy_recording > FORMAT_VERSION {
+                bail!(
+                    "{} is a version {} recording; this dashy reads up to version {}",
+                    path.display(),
+                    header.dashy_recording,
+                    FORMAT_VERSION
+                );
+            }
+            host = host.or(header.host);
+        } else if let Ok(sample) = serde_json::from_str::<Sample>(&line) {
+            samples.push(sample);
+        }
+    }
+    if samples.is_empty() {
+        bail!("no samples in {}", path.display());
+    }
+    Ok((host, samples))
+}
+
+/// When an existing recording was started, from its header. Files from before the header had
+/// the time fall back to the file's birth time, or its last change where that isn't available.
+fn started_at(path: &Path) -> io::Result<SystemTime> {
+    let file = File::open(path)?;
+    let mut first = String::new();
+    BufReader::new(&file).read_line(&mut first)?;
+    if let Some(started_ms) = serde_json::from_str::<Header>(&first).ok().and_then(|h| h.started_ms) {
+        return Ok(UNIX_EPOCH + Duration::from_millis(started_ms));
+    }
+    let metadata = file.metadata()?;
+    Ok(metadata.created().or_else(|_| metadata.modified()).unwrap_or_else(|_| SystemTime::now()))
+}
+
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// `dashy.rec` -> `dashy.rec.1`
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test, and the arguments to record into it.
+    fn setup(name: &str, max_size: u64, max_age: Duration, keep: usize) -> (PathBuf, RecordArgs) {
+        let dir = std::env::temp_dir().join(format!("dashy-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let args = RecordArgs {
+            record: Some(dir.join("dashy.jsonl")),
+            record_max_size: max_size,
+            record_max_age: max_age,
+            record_keep: keep,
+        };
+        (dir, args)
+    }
+
+    fn timestamps(path: &Path) -> Vec<u64> {
+        read(path).unwrap().1.iter().map(|s| s.timestamp_ms).collect()
+    }
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn rotates_by_size_and_shifts_older_files() {
+        let (dir, args) = setup("shift", 1, HOUR, 2);
+        let path = dir.join("dashy.jsonl");
+        let mut recorder = Recorder::from_args(&args).unwrap().unwrap();
+        for timestamp in 1..=3 {
+            recorder.write(&Sample::at(timestamp)).unwrap();
+        }
+
+        assert_eq!(timestamps(&path), [3]);
+        assert_eq!(timestamps(&rotated(&path, 1)), [2]);
+        assert_eq!(timestamps(&rotated(&path, 2)), [1]);
+        assert!(!rotated(&path, 3).exists());
+        // Every file starts with its own header
+        let first = fs::read_to_string(rotated(&path, 1)).unwrap();
+        assert!(first.starts_with("{\"dashy_recording\":1,"), "{}", first);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_no_rotated_files() {
+        let (dir, args) = setup("keep0", 1, HOUR, 0);
+        let path = dir.join("dashy.jsonl");
+        let mut recorder = Recorder::from_args(&args).unwrap().unwrap();
+        recorder.write(&Sample::at(1)).unwrap();
+        recorder.write(&Sample::at(2)).unwrap();
+
+        assert_eq!(timestamps(&path), [2]);
+        assert!(!rotated(&path, 1).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn appends_until_size_limit() {
+        let (dir, args) = setup("append", 1 << 20, HOUR, 2);
+        let path = dir.join("dashy.jsonl");
+        Recorder::from_args(&args).unwrap().unwrap().write(&Sample::at(1)).unwrap();
+        Recorder::from_args(&args).unwrap().unwrap().write(&Sample::at(2)).unwrap();
+
+        assert_eq!(timestamps(&path), [1, 2]);
+        // One header, not one per reopen
+        assert_eq!(fs::read_to_string(&path).unwrap().matches("dashy_recording").count(), 1);
+        assert!(!rotated(&path, 1).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_by_age_of_reopened_file() {
+        let (dir, args) = setup("age", 1 << 20, Duration::from_millis(200), 2);
+        let path = dir.join("dashy.jsonl");
+        Recorder::from_args(&args).unwrap().unwrap().write(&Sample::at(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+
+        // The file's age counts from when it was started, not from when it was reopened
+        let mut recorder = Recorder::from_args(&args).unwrap().unwrap();
+        recorder.write(&Sample::at(2)).unwrap();
+        recorder.write(&Sample::at(3)).unwrap();
+
+        assert_eq!(timestamps(&rotated(&path, 1)), [1]);
+        assert_eq!(timestamps(&path), [2, 3]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reads_start_time_from_header() {
+        let (dir, args) = setup("started", 1 << 20, HOUR, 2);
+        let path = dir.join("dashy.jsonl");
+        // Started two hours ago, though the file itself is brand new
+        let started_ms = (SystemTime::now() - 2 * HOUR).duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let header = Header { dashy_recording: FORMAT_VERSION, host: None, started_ms: Some(started_ms) };
+        fs::write(&path, format!("{}\n", serde_json::to_string(&header).unwrap())).unwrap();
+        Recorder::from_args(&args).unwrap().unwrap().write(&Sample::at(1)).unwrap();
+
+        // Rotated right away rather than two hours after the reopen
+        assert!(rotated(&path, 1).exists());
+        assert_eq!(timestamps(&path), [1]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,216 @@
+//! Playback of `--record`ed files through the dashboard: samples are fed to `App::update` at
+//! the pace they were recorded (times a speed factor), with pause, step and seek.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use crossterm::event::KeyCode;
+
+use crate::app::{App, ReplayStatus, HISTORY_LEN};
+use crate::record;
+use crate::system::Sample;
+
+/// Longer stretches without samples (dashy wasn't running) are played as this long.
+const MAX_GAP: Duration = Duration::from_secs(5);
+pub const MIN_SPEED: f64 = 0.25;
+pub const MAX_SPEED: f64 = 64.0;
+/// How far `[` and `]` jump.
+const SEEK_MS: u64 = 60_000;
+
+pub struct Replay {
+    host: Option<String>,
+    samples: Vec<Sample>,
+    /// Index of the sample on screen
+    position: usize,
+    paused: bool,
+    speed: f64,
+    /// When the sample on screen was applied
+    shown_at: Instant,
+}
+
+impl Replay {
+    /// `speed` is expected to be within `MIN_SPEED..=MAX_SPEED`, as `--speed` checks.
+    pub fn load(path: &Path, speed: f64) -> Result<Self> {
+        let (host, samples) = record::read(path)?;
+        Ok(Self {
+            host,
+            samples,
+            position: 0,
+            paused: false,
+            speed,
+            shown_at: Instant::now(),
+        })
+    }
+
+    pub fn status(&self) -> ReplayStatus {
+        ReplayStatus {
+            host: self.host.clone(),
+            position: self.position,
+            len: self.samples.len(),
+            paused: self.paused,
+            speed: self.speed,
+        }
+    }
+
+    /// How long until the next sample is due; the event loop waits at most this long.
+    pub fn time_until_next(&self) -> Duration {
+        match self.next_gap() {
+            Some(gap) if !self.paused => gap.saturating_sub(self.shown_at.elapsed()),
+            _ => Duration::from_millis(250),
+        }
+    }
+
+    /// Apply the next sample if it's due. Playback pauses by itself at the end.
+    pub fn tick(&mut self, app: &mut App) {
+        let Some(gap) = self.next_gap() else {
+            self.paused = true;
+            return;
+        };
+        if !self.paused && self.shown_at.elapsed() >= gap {
+            self.position += 1;
+            app.update(self.samples[self.position].clone());
+            self.shown_at = Instant::now();
+        }
+    }
+
+    /// Handle a playback key, returning false for keys that aren't one.
+    pub fn handle_key(&mut self, app: &mut App, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('p') => {
+                self.paused = !self.paused;
+                self.shown_at = Instant::now();
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+            KeyCode::Char('-') => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            KeyCode::Char('.') => {
+                self.paused = true;
+                self.seek(app, self.position + 1);
+            }
+            KeyCode::Char(',') => {
+                self.paused = true;
+                self.seek(app, self.position.saturating_sub(1));
+            }
+            KeyCode::Char(']') => {
+                let target = self.samples[self.position].timestamp_ms + SEEK_MS;
+                let index = self.samples.partition_point(|s| s.timestamp_ms < target);
+                self.seek(app, index);
+            }
+            KeyCode::Char('[') => {
+                let target = self.samples[self.position].timestamp_ms.saturating_sub(SEEK_MS);
+                let index = self.samples.partition_point(|s| s.timestamp_ms < target);
+                self.seek(app, index);
+            }
+            KeyCode::Home => self.seek(app, 0),
+            KeyCode::End => self.seek(app, usize::MAX),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Show sample `index` (clamped to the recording), rebuilding the history graphs and rates
+    /// from the samples before it.
+    pub fn seek(&mut self, app: &mut App, index: usize) {
+        let index = index.min(self.samples.len() - 1);
+        app.clear_history();
+        // One extra sample so the oldest point on the graphs has a rate too
+        for sample in &self.samples[index.saturating_sub(HISTORY_LEN)..=index] {
+            app.update(sample.clone());
+        }
+        self.position = index;
+        self.shown_at = Instant::now();
+    }
+
+    /// Time between the sample on screen and the next one at the current speed.
+    fn next_gap(&self) -> Option<Duration> {
+        let (current, next) = (self.samples.get(self.position)?, self.samples.get(self.position + 1)?);
+        let gap = Duration::from_millis(next.timestamp_ms.saturating_sub(current.timestamp_ms)).min(MAX_GAP);
+        Some(gap.div_f64(self.speed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` samples a second apart, reading 1000 bytes/s from disk, shown from the first.
+    fn replay(count: u64) -> (Replay, App) {
+        let samples = (0..count).map(|i| Sample { disk: (1000 * (i + 1), 0), ..Sample::at(1000 * i) }).collect();
+        let mut replay =
+            Replay { host: None, samples, position: 0, paused: false, speed: 1.0, shown_at: Instant::now() };
+        let mut app = App::new();
+        replay.seek(&mut app, 0);
+        (replay, app)
+    }
+
+    /// Pretend the sample on screen has been up long enough for the next one to be due.
+    fn wait(replay: &mut Replay) {
+        replay.shown_at = Instant::now() - MAX_GAP;
+    }
+
+    #[test]
+    fn plays_until_the_end_and_pauses() {
+        let (mut replay, mut app) = replay(3);
+        replay.tick(&mut app);
+        assert_eq!(replay.position, 0, "played before the next sample was due");
+
+        wait(&mut replay);
+        replay.tick(&mut app);
+        assert_eq!((replay.position, app.sample_time_ms()), (1, Some(1000)));
+
+        replay.handle_key(&mut app, KeyCode::Char('p'));
+        wait(&mut replay);
+        replay.tick(&mut app);
+        assert_eq!(replay.position, 1, "played while paused");
+
+        replay.handle_key(&mut app, KeyCode::Char('p'));
+        wait(&mut replay);
+        replay.tick(&mut app);
+        assert_eq!((replay.position, app.sample_time_ms()), (2, Some(2000)));
+        assert!(!replay.paused);
+        replay.tick(&mut app);
+        assert!(replay.paused);
+        assert_eq!(replay.position, 2);
+    }
+
+    #[test]
+    fn seeks_with_keys() {
+        let (mut replay, mut app) = replay(200);
+        let mut press = |key| {
+            assert!(replay.handle_key(&mut app, key));
+            (replay.position, app.sample_time_ms().unwrap())
+        };
+        // A minute at a time
+        assert_eq!(press(KeyCode::Char(']')), (60, 60_000));
+        assert_eq!(press(KeyCode::Char(']')), (120, 120_000));
+        assert_eq!(press(KeyCode::Char('[')), (60, 60_000));
+        assert_eq!(press(KeyCode::Char('.')), (61, 61_000));
+        assert_eq!(press(KeyCode::Char(',')), (60, 60_000));
+        assert_eq!(press(KeyCode::Char('[')), (0, 0));
+        assert_eq!(press(KeyCode::Char('[')), (0, 0));
+        assert_eq!(press(KeyCode::End), (199, 199_000));
+        assert_eq!(press(KeyCode::Char(']')), (199, 199_000));
+        assert_eq!(press(KeyCode::Char('.')), (199, 199_000));
+        assert_eq!(press(KeyCode::Home), (0, 0));
+        // Stepping pauses playback
+        assert!(replay.paused);
+        assert!(!replay.handle_key(&mut app, KeyCode::Char('x')));
+    }
+
+    #[test]
+    fn rebuilds_history_when_seeking() {
+        let (mut replay, mut app) = replay(200);
+        replay.seek(&mut app, 100);
+        // Only the last HISTORY_LEN samples are kept, and the one before them gives the oldest a rate
+        let times: Vec<u64> = (41..=100).map(|i| 1000 * i).collect();
+        assert_eq!(app.history_times, times);
+        assert_eq!(app.rated_history_start(), 0);
+        assert!(app.disk_read_history.iter().all(|&rate| rate == 1000), "{:?}", app.disk_read_history);
+
+        // Near the start, the first sample is still on the graphs without a rate
+        replay.seek(&mut app, 5);
+        assert_eq!(app.history_times, [0, 1000, 2000, 3000, 4000, 5000]);
+        assert_eq!(app.rated_history_start(), 1);
+        assert_eq!(app.disk_read_history, [0, 1000, 1000, 1000, 1000, 1000]);
+    }
+}
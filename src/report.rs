@@ -0,0 +1,267 @@
+//! Machine-readable form of what the dashboard shows, for the non-interactive modes. Rates come
+//! from `App`, so they're computed exactly as on screen.
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::app::App;
+
+/// Bumped whenever a field is renamed or removed; new fields may be added without a bump.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Metric groups that can be picked for a report.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Metric {
+    Cpu,
+    Memory,
+    Disk,
+    Network,
+    Tcp,
+    Ports,
+}
+
+/// Groups left out of a report are omitted from the JSON rather than null.
+#[derive(Serialize)]
+pub struct Report {
+    pub version: u32,
+    /// Unix time in milliseconds of the sample
+    pub timestamp_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CpuReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk: Option<DiskReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<TcpReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ports: Option<Vec<PortReport>>,
+}
+
+#[derive(Serialize)]
+pub struct CpuReport {
+    pub usage_percent: f64,
+}
+
+#[derive(Serialize)]
+pub struct MemoryReport {
+    pub used_bytes: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct DiskReport {
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
+}
+
+#[derive(Serialize)]
+pub struct NetworkReport {
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    pub interfaces: Vec<InterfaceReport>,
+}
+
+#[derive(Serialize)]
+pub struct InterfaceReport {
+    pub name: String,
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    pub rx_packets_per_sec: u64,
+    pub tx_packets_per_sec: u64,
+    pub rx_errors_per_sec: u64,
+    pub tx_errors_per_sec: u64,
+    pub rx_drops_per_sec: u64,
+    pub tx_drops_per_sec: u64,
+}
+
+/// Kernel TCP/UDP counters turned into per-second rates.
+#[derive(Serialize)]
+pub struct TcpReport {
+    pub retransmits_per_sec: u64,
+    pub active_opens_per_sec: u64,
+    pub passive_opens_per_sec: u64,
+    pub estab_resets_per_sec: u64,
+    pub out_resets_per_sec: u64,
+    pub listen_overflows_per_sec: u64,
+    pub listen_drops_per_sec: u64,
+    pub udp_in_errors_per_sec: u64,
+    pub udp_rcvbuf_errors_per_sec: u64,
+}
+
+#[derive(Serialize)]
+pub struct PortReport {
+    pub port: u16,
+    pub pid: u32,
+    pub process: String,
+    /// Smart label, e.g. `node my-app/index.js`
+    pub label: String,
+    pub command: String,
+    /// Container, pod or systemd unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+/// Describe the latest sample applied to `app`, limited to `metrics` (every group if empty).
+pub fn build(app: &App, metrics: &[Metric]) -> Report {
+    let wants = |metric: Metric| metrics.is_empty() || metrics.contains(&metric);
+    let protocols = &app.protocol_rates;
+
+    Report {
+        version: SCHEMA_VERSION,
+        timestamp_ms: app.sample_time_ms().unwrap_or(0),
+        cpu: wants(Metric::Cpu).then(|| CpuReport {
+            usage_percent: app.cpu_history.back().copied().unwrap_or(0.0),
+        }),
+        memory: wants(Metric::Memory).then_some(MemoryReport {
+            used_bytes: app.memory_used,
+            total_bytes: app.memory_total,
+        }),
+        disk: wants(Metric::Disk).then_some(DiskReport {
+            read_bytes_per_sec: app.disk_read,
+            write_bytes_per_sec: app.disk_write,
+        }),
+        network: wants(Metric::Network).then(|| NetworkReport {
+            rx_bytes_per_sec: app.network_rx,
+            tx_bytes_per_sec: app.network_tx,
+            interfaces: app
+                .network_interfaces
+                .iter()
+                .map(|iface| InterfaceReport {
+                    name: iface.name.clone(),
+                    rx_bytes_per_sec: iface.rx,
+                    tx_bytes_per_sec: iface.tx,
+                    rx_packets_per_sec: iface.rx_packets,
+                    tx_packets_per_sec: iface.tx_packets,
+                    rx_errors_per_sec: iface.rx_errors,
+                    tx_errors_per_sec: iface.tx_errors,
+                    rx_drops_per_sec: iface.rx_drops,
+                    tx_drops_per_sec: iface.tx_drops,
+                })
+                .collect(),
+        }),
+        tcp: wants(Metric::Tcp).then_some(TcpReport {
+            retransmits_per_sec: protocols.tcp_retransmits,
+            active_opens_per_sec: protocols.tcp_active_opens,
+            passive_opens_per_sec: protocols.tcp_passive_opens,
+            estab_resets_per_sec: protocols.tcp_estab_resets,
+            out_resets_per_sec: protocols.tcp_out_resets,
+            listen_overflows_per_sec: protocols.tcp_listen_overflows,
+            listen_drops_per_sec: protocols.tcp_listen_drops,
+            udp_in_errors_per_sec: protocols.udp_in_errors,
+            udp_rcvbuf_errors_per_sec: protocols.udp_rcvbuf_errors,
+        }),
+        ports: wants(Metric::Ports).then(|| {
+            app.open_ports
+                .iter()
+                .map(|(port, process, pid)| {
+                    let info = app.processes.iter().find(|p| p.pid == *pid);
+                    PortReport {
+                        port: *port,
+                        pid: *pid,
+                        process: process.clone(),
+                        label: info.map(|p| p.label.clone()).unwrap_or_else(|| process.clone()),
+                        command: info.map(|p| p.command.clone()).unwrap_or_default(),
+                        owner: info.and_then(|p| p.owner.as_ref()).map(|o| o.to_string()),
+                    }
+                })
+                .collect()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{InterfaceIo, ProtocolCounters, Sample};
+    use serde_json::{json, Value};
+
+    fn sample(timestamp_ms: u64, bytes: u64) -> Sample {
+        Sample {
+            cpu: 25.0,
+            memory: (1024, 4096),
+            disk: (bytes, bytes),
+            network: vec![InterfaceIo {
+                name: "eth0".to_string(),
+                rx_bytes: bytes,
+                tx_bytes: bytes,
+                rx_packets: 0,
+                tx_packets: 0,
+                rx_errors: 0,
+                tx_errors: 0,
+                rx_drops: 0,
+                tx_drops: 0,
+            }],
+            protocols: ProtocolCounters::default(),
+            ports: vec![(8080, "node".to_string(), 4242)],
+            ..Sample::at(timestamp_ms)
+        }
+    }
+
+    fn keys(value: &Value) -> Vec<&str> {
+        value.as_object().unwrap().keys().map(String::as_str).collect()
+    }
+
+    /// Consumers parse these names, so renaming one needs a `SCHEMA_VERSION` bump.
+    #[test]
+    fn keeps_schema() {
+        let mut app = App::new();
+        app.update(sample(1_700_000_000_000, 1024));
+        app.update(sample(1_700_000_001_000, 3072));
+        let report = serde_json::to_value(build(&app, &[])).unwrap();
+
+        assert_eq!(SCHEMA_VERSION, 1);
+        assert_eq!(report["version"], json!(SCHEMA_VERSION));
+        assert_eq!(report["timestamp_ms"], json!(1_700_000_001_000u64));
+        assert_eq!(keys(&report), ["cpu", "disk", "memory", "network", "ports", "tcp", "timestamp_ms", "version"]);
+        assert_eq!(keys(&report["cpu"]), ["usage_percent"]);
+        assert_eq!(keys(&report["memory"]), ["total_bytes", "used_bytes"]);
+        assert_eq!(keys(&report["disk"]), ["read_bytes_per_sec", "write_bytes_per_sec"]);
+        assert_eq!(keys(&report["network"]), ["interfaces", "rx_bytes_per_sec", "tx_bytes_per_sec"]);
+        assert_eq!(
+            keys(&report["network"]["interfaces"][0]),
+            [
+                "name",
+                "rx_bytes_per_sec",
+                "rx_drops_per_sec",
+                "rx_errors_per_sec",
+                "rx_packets_per_sec",
+                "tx_bytes_per_sec",
+                "tx_drops_per_sec",
+                "tx_errors_per_sec",
+                "tx_packets_per_sec",
+            ]
+        );
+        assert_eq!(
+            keys(&report["tcp"]),
+            [
+                "active_opens_per_sec",
+                "estab_resets_per_sec",
+                "listen_drops_per_sec",
+                "listen_overflows_per_sec",
+                "out_resets_per_sec",
+                "passive_opens_per_sec",
+                "retransmits_per_sec",
+                "udp_in_errors_per_sec",
+                "udp_rcvbuf_errors_per_sec",
+            ]
+        );
+        // No process info for the port, so there's no owner either
+        assert_eq!(keys(&report["ports"][0]), ["command", "label", "pid", "port", "process"]);
+
+        assert_eq!(report["cpu"]["usage_percent"], json!(25.0));
+        assert_eq!(report["disk"]["read_bytes_per_sec"], json!(2048));
+        assert_eq!(report["network"]["interfaces"][0]["rx_bytes_per_sec"], json!(2048));
+        assert_eq!(report["ports"][0]["label"], json!("node"));
+    }
+
+    #[test]
+    fn omits_unselected_metrics() {
+        let mut app = App::new();
+        app.update(sample(1_700_000_000_000, 1024));
+        let report = serde_json::to_value(build(&app, &[Metric::Cpu, Metric::Ports])).unwrap();
+        assert_eq!(keys(&report), ["cpu", "ports", "timestamp_ms", "version"]);
+    }
+}
@@ -1,6 +1,13 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::process::Command;
-use sysinfo::{Disks, Networks, System};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Disks, Groups, Networks, Pid, Process, System, Users};
+
+use crate::cgroup::{self, CgroupStats, Owner};
+use crate::config::{Config, NetworkConfig};
+use crate::procfs::{self, SocketCounts, SocketInfo};
 
 pub fn get_cpu_usage(sys: &System) -> f64 {
     sys.global_cpu_usage() as f64
@@ -13,30 +20,9 @@ pub fn get_memory(sys: &System) -> (u64, u64) {
 pub fn get_disk_io(_disks: &Disks) -> (u64, u64) {
     // Use /proc/diskstats on Linux or ioreg on macOS
     if cfg!(target_os = "linux") {
-        if let Ok(content) = std::fs::read_to_string("/proc/diskstats") {
-            let mut read_bytes = 0u64;
-            let mut write_bytes = 0u64;
-            for line in content.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 14 {
-                    // Only count real disks (sd*, nvme*, vd*), not partitions
-                    let name = parts[2];
-                    if name.starts_with("sd") || name.starts_with("nvme") || name.starts_with("vd") {
-                        // Skip partitions (e.g., sda1, nvme0n1p1)
-                        let is_partition = name.chars().last().map(|c| c.is_numeric()).unwrap_or(false)
-                            && name.contains(|c: char| c.is_numeric());
-                        if !is_partition || name.starts_with("nvme") {
-                            // Fields: reads completed (3), sectors read (5), writes completed (7), sectors written (9)
-                            if let (Ok(read_sectors), Ok(write_sectors)) =
-                                (parts[5].parse::<u64>(), parts[9].parse::<u64>())
-                            {
-                                read_bytes += read_sectors * 512;
-                                write_bytes += write_sectors * 512;
-                            }
-                        }
-                    }
-                }
-            }
+        if let Some(devices) = read_diskstats() {
+            let read_bytes = devices.iter().map(|d| d.read_bytes).sum();
+            let write_bytes = devices.iter().map(|d| d.write_bytes).sum();
             return (read_bytes, write_bytes);
         }
     } else if cfg!(target_os = "macos") {
@@ -81,19 +67,560 @@ pub fn get_disk_io(_disks: &Disks) -> (u64, u64) {
     (0, 0)
 }
 
-pub fn get_network_io(networks: &Networks) -> (u64, u64) {
-    let mut total_rx = 0u64;
-    let mut total_tx = 0u64;
+/// Cumulative bytes read and written by one disk.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DiskIo {
+    pub name: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Per-disk counters, Linux only (macOS reports its totals without device names).
+pub fn get_disk_devices() -> Vec<DiskIo> {
+    if cfg!(target_os = "linux") {
+        read_diskstats().unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+fn read_diskstats() -> Option<Vec<DiskIo>> {
+    let content = std::fs::read_to_string("/proc/diskstats").ok()?;
+    let mut devices = Vec::new();
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 14 {
+            // Only count real disks (sd*, nvme*, vd*), not partitions
+            let name = parts[2];
+            if name.starts_with("sd") || name.starts_with("nvme") || name.starts_with("vd") {
+                // Skip partitions (e.g., sda1, nvme0n1p1)
+                let is_partition = name.chars().last().map(|c| c.is_numeric()).unwrap_or(false)
+                    && name.contains(|c: char| c.is_numeric());
+                if !is_partition || name.starts_with("nvme") {
+                    // Fields: reads completed (3), sectors read (5), writes completed (7), sectors written (9)
+                    if let (Ok(read_sectors), Ok(write_sectors)) = (parts[5].parse::<u64>(), parts[9].parse::<u64>()) {
+                        devices.push(DiskIo {
+                            name: name.to_string(),
+                            read_bytes: read_sectors * 512,
+                            write_bytes: write_sectors * 512,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Some(devices)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InterfaceIo {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+}
+
+pub fn get_network_io(networks: &Networks, filter: &NetworkConfig) -> Vec<InterfaceIo> {
+    // sysinfo doesn't expose drop counters, so read them from /proc/net/dev on Linux
+    let drops = if cfg!(target_os = "linux") {
+        read_interface_drops()
+    } else {
+        HashMap::new()
+    };
+
+    let mut interfaces: Vec<InterfaceIo> = networks
+        .list()
+        .iter()
+        .filter(|(name, _)| filter.is_shown(name))
+        .map(|(name, data)| {
+            let (rx_drops, tx_drops) = drops.get(name).copied().unwrap_or((0, 0));
+            InterfaceIo {
+                name: name.clone(),
+                rx_bytes: data.total_received(),
+                tx_bytes: data.total_transmitted(),
+                rx_packets: data.total_packets_received(),
+                tx_packets: data.total_packets_transmitted(),
+                rx_errors: data.total_errors_on_received(),
+                tx_errors: data.total_errors_on_transmitted(),
+                rx_drops,
+                tx_drops,
+            }
+        })
+        .collect();
+
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+fn read_interface_drops() -> HashMap<String, (u64, u64)> {
+    let mut drops = HashMap::new();
+
+    if let Ok(content) = std::fs::read_to_string("/proc/net/dev") {
+        // Format: iface: rx_bytes rx_packets rx_errs rx_drop ... tx_bytes tx_packets tx_errs tx_drop ...
+        for line in content.lines().skip(2) {
+            let Some((name, counters)) = line.split_once(':') else {
+                continue;
+            };
+            let fields: Vec<u64> = counters
+                .split_whitespace()
+                .filter_map(|f| f.parse().ok())
+                .collect();
+            if fields.len() >= 12 {
+                drops.insert(name.trim().to_string(), (fields[3], fields[11]));
+            }
+        }
+    }
+
+    drops
+}
+
+pub struct InterfaceDetails {
+    pub name: String,
+    pub mac: String,
+    pub addresses: Vec<String>,
+    pub operstate: String,
+    pub speed_mbps: Option<u64>,
+    pub duplex: Option<String>,
+    pub mtu: u64,
+}
+
+pub fn get_interface_details(networks: &Networks, name: &str) -> InterfaceDetails {
+    let data = networks.list().get(name);
+    let mut addresses: Vec<String> = data
+        .map(|d| d.ip_networks().iter().map(|ip| ip.to_string()).collect())
+        .unwrap_or_default();
+    // IPv4 first, then IPv6
+    addresses.sort_by_key(|addr| addr.contains(':'));
+
+    // Link state, speed and duplex only exist in sysfs
+    let sysfs = |attr: &str| -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        std::fs::read_to_string(format!("/sys/class/net/{}/{}", name, attr))
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty() && v != "unknown")
+    };
+
+    InterfaceDetails {
+        name: name.to_string(),
+        mac: data.map(|d| d.mac_address().to_string()).unwrap_or_default(),
+        addresses,
+        operstate: sysfs("operstate").unwrap_or_else(|| "unknown".to_string()),
+        // Virtual and down links report -1 or fail to read
+        speed_mbps: sysfs("speed").and_then(|v| v.parse().ok()),
+        duplex: sysfs("duplex"),
+        mtu: data.map(|d| d.mtu()).unwrap_or(0),
+    }
+}
+
+/// Cumulative kernel TCP/UDP counters. Also used for their per-second rates.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ProtocolCounters {
+    pub tcp_retransmits: u64,
+    pub tcp_active_opens: u64,
+    pub tcp_passive_opens: u64,
+    pub tcp_estab_resets: u64,
+    pub tcp_out_resets: u64,
+    pub tcp_listen_overflows: u64,
+    pub tcp_listen_drops: u64,
+    pub udp_in_errors: u64,
+    pub udp_rcvbuf_errors: u64,
+}
+
+pub fn get_protocol_counters() -> ProtocolCounters {
+    if !cfg!(target_os = "linux") {
+        return ProtocolCounters::default();
+    }
+
+    let mut stats = HashMap::new();
+    for path in ["/proc/net/snmp", "/proc/net/netstat"] {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            parse_snmp_table(&content, &mut stats);
+        }
+    }
+
+    let get = |key: &str| stats.get(key).copied().unwrap_or(0);
+    ProtocolCounters {
+        tcp_retransmits: get("Tcp.RetransSegs"),
+        tcp_active_opens: get("Tcp.ActiveOpens"),
+        tcp_passive_opens: get("Tcp.PassiveOpens"),
+        tcp_estab_resets: get("Tcp.EstabResets"),
+        tcp_out_resets: get("Tcp.OutRsts"),
+        tcp_listen_overflows: get("TcpExt.ListenOverflows"),
+        tcp_listen_drops: get("TcpExt.ListenDrops"),
+        udp_in_errors: get("Udp.InErrors"),
+        udp_rcvbuf_errors: get("Udp.RcvbufErrors"),
+    }
+}
+
+/// Parse the paired header/value lines of /proc/net/snmp and /proc/net/netstat into
+/// `"Group.Field"` keys, e.g. `Tcp.RetransSegs`.
+fn parse_snmp_table(content: &str, stats: &mut HashMap<String, u64>) {
+    let mut lines = content.lines();
+    while let (Some(header), Some(values)) = (lines.next(), lines.next()) {
+        let (Some((group, names)), Some((_, values))) = (header.split_once(':'), values.split_once(':')) else {
+            continue;
+        };
+        for (name, value) in names.split_whitespace().zip(values.split_whitespace()) {
+            // Some fields (e.g. Tcp MaxConn) are signed; they aren't counters we use
+            if let Ok(value) = value.parse::<u64>() {
+                stats.insert(format!("{}.{}", group, name), value);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub user: String,
+    pub uid: Option<u32>,
+    pub cpu: f32,
+    pub memory: u64,
+    pub state: String,
+    pub threads: usize,
+    pub command: String,
+    pub cwd: Option<String>,
+    /// Container, pod or systemd unit the process runs in
+    pub owner: Option<Owner>,
+    /// Short description that tells apart processes with the same name, e.g. `node my-app/index.js`
+    pub label: String,
+    pub sockets: SocketCounts,
+}
+
+pub fn get_processes(sys: &System, users: &Users) -> Vec<ProcessInfo> {
+    let sockets = read_sockets();
+    sys.processes()
+        .values()
+        // On Linux sysinfo also lists each thread as its own entry
+        .filter(|process| process.thread_kind().is_none())
+        .map(|process| process_info(process, users, &sockets))
+        .collect()
+}
+
+/// Host sockets by inode, for attributing them to processes. Empty outside Linux.
+fn read_sockets() -> HashMap<u64, SocketInfo> {
+    if cfg!(target_os = "linux") {
+        procfs::read_sockets()
+    } else {
+        HashMap::new()
+    }
+}
+
+fn process_info(process: &Process, users: &Users, sockets: &HashMap<u64, SocketInfo>) -> ProcessInfo {
+    let name = process.name().to_string_lossy().to_string();
+    let user = process
+        .user_id()
+        .map(|uid| {
+            users
+                .get_user_by_id(uid)
+                .map(|user| user.name().to_string())
+                .unwrap_or_else(|| (**uid).to_string())
+        })
+        .unwrap_or_default();
+    let args: Vec<String> = process.cmd().iter().map(|arg| arg.to_string_lossy().to_string()).collect();
+    let cwd = process.cwd().map(|p| p.display().to_string());
+    let label = smart_label(&name, &args, cwd.as_deref());
+    let command = if process.cmd().is_empty() {
+        format!("[{}]", name)
+    } else {
+        args.join(" ")
+    };
+
+    ProcessInfo {
+        pid: process.pid().as_u32(),
+        parent: process.parent().map(|pid| pid.as_u32()),
+        name,
+        user,
+        uid: process.user_id().map(|uid| **uid),
+        cpu: process.cpu_usage(),
+        memory: process.memory(),
+        state: process.status().to_string(),
+        // `tasks` lists the threads besides the main one
+        threads: process.tasks().map(|tasks| tasks.len() + 1).unwrap_or(1),
+        command,
+        cwd,
+        owner: if cfg!(target_os = "linux") {
+            cgroup::owner_of(process.pid().as_u32())
+        } else {
+            None
+        },
+        label,
+        sockets: procfs::count_sockets(process.pid().as_u32(), sockets),
+    }
+}
+
+/// Runtimes whose process name says nothing about what they're running.
+const INTERPRETERS: [&str; 14] = [
+    "node", "python", "python3", "ruby", "java", "deno", "bun", "php", "perl", "dotnet", "npm", "npx", "yarn", "pnpm",
+];
+
+/// Launchers whose arguments (e.g. `run dev`) are the interesting part.
+const PACKAGE_MANAGERS: [&str; 4] = ["npm", "npx", "yarn", "pnpm"];
+
+/// Entry point names that only make sense alongside their project directory.
+const GENERIC_SCRIPTS: [&str; 7] = ["index", "main", "server", "app", "manage", "cli", "run"];
+
+/// Derive a label for interpreter processes from the script, module or jar they run, falling
+/// back to the project directory. Other processes keep their name.
+fn smart_label(name: &str, args: &[String], cwd: Option<&str>) -> String {
+    let runtime = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if !INTERPRETERS.contains(&runtime) {
+        return name.to_string();
+    }
+
+    let file_name = |path: &str| {
+        Path::new(path)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string())
+    };
+    let project = cwd.map(file_name);
+
+    let with_project = |label: String| match &project {
+        Some(project) => format!("{} ({})", label, project),
+        None => label,
+    };
+
+    // Find the entry point: the first positional argument, skipping interpreter options
+    let mut target = None;
+    let mut rest = args.iter().enumerate().skip(1);
+    while let Some((idx, arg)) = rest.next() {
+        match arg.as_str() {
+            // Options whose value isn't the entry point (java's `-p` is the module path)
+            "-cp" | "-classpath" | "--class-path" | "-r" | "--require" | "-W" | "-X" => {
+                rest.next();
+            }
+            "-p" | "--module-path" if runtime == "java" => {
+                rest.next();
+            }
+            // `python -c`, `node -e`/`-p`, `ruby -e`: the code is inline, there's no entry point
+            "-c" | "-e" | "-p" | "--eval" | "--print" => {
+                return with_project(format!("{} {} <inline>", name, arg));
+            }
+            // `python -m module` and `java -jar app.jar` name the entry point in the next arg
+            "-m" | "-jar" => {
+                target = rest.next();
+                break;
+            }
+            _ if arg.starts_with('-') => {}
+            _ => {
+                target = Some((idx, arg));
+                break;
+            }
+        }
+    }
+
+    // Package managers, whether run directly or via `node /usr/bin/npm`: keep the subcommand
+    let manager = if PACKAGE_MANAGERS.contains(&runtime) {
+        Some((runtime.to_string(), 1))
+    } else {
+        target
+            .map(|(idx, t)| (file_name(t), idx + 1))
+            .filter(|(file, _)| PACKAGE_MANAGERS.contains(&file.as_str()))
+    };
+    if let Some((manager, first_arg)) = manager {
+        let subcommand = args.get(first_arg..).unwrap_or_default().join(" ");
+        return with_project(format!("{} {}", manager, subcommand).trim_end().to_string());
+    }
+
+    let Some((_, target)) = target else {
+        return with_project(name.to_string());
+    };
+
+    let path = Path::new(target);
+    let file = file_name(target);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    if !GENERIC_SCRIPTS.contains(&stem.as_str()) {
+        return format!("{} {}", name, file);
+    }
+
+    // `index.js` alone is meaningless: prefer the script's own directory, then the working directory
+    let dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|d| d.to_string_lossy().to_string())
+        .or(project);
+    match dir {
+        Some(dir) => format!("{} {}/{}", name, dir, file),
+        None => format!("{} {}", name, file),
+    }
+}
+
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub command: String,
+    pub exe: Option<String>,
+    pub cwd: Option<String>,
+    pub user: String,
+    pub group: String,
+    pub owner: Option<Owner>,
+    pub start_time: u64,
+    pub run_time: u64,
+    pub threads: usize,
+    pub open_fds: Option<usize>,
+    pub limits: Vec<(String, String, String)>,
+    pub environ: Vec<String>,
+    pub sockets: Vec<SocketInfo>,
+    pub socket_counts: SocketCounts,
+}
+
+pub fn get_process_details(sys: &System, users: &Users, pid: u32) -> Option<ProcessDetails> {
+    let process = sys.process(Pid::from_u32(pid))?;
+    let all_sockets = read_sockets();
+    let info = process_info(process, users, &all_sockets);
+
+    let group = process
+        .group_id()
+        .map(|gid| {
+            Groups::new_with_refreshed_list()
+                .iter()
+                .find(|group| *group.id() == gid)
+                .map(|group| group.name().to_string())
+                .unwrap_or_else(|| (*gid).to_string())
+        })
+        .unwrap_or_default();
+
+    let inodes = procfs::socket_inodes(pid);
+    let mut sockets: Vec<SocketInfo> = all_sockets
+        .into_iter()
+        .filter(|(inode, _)| inodes.contains(inode))
+        .map(|(_, socket)| socket)
+        .collect();
+    sockets.sort_by(|a, b| (a.protocol, &a.local).cmp(&(b.protocol, &b.local)));
 
-    for (_name, data) in networks.list() {
-        total_rx += data.total_received();
-        total_tx += data.total_transmitted();
+    Some(ProcessDetails {
+        pid,
+        parent: info.parent,
+        name: info.name,
+        command: info.command,
+        exe: process.exe().map(|p| p.display().to_string()),
+        cwd: info.cwd,
+        user: info.user,
+        group,
+        owner: info.owner,
+        start_time: process.start_time(),
+        run_time: process.run_time(),
+        threads: info.threads,
+        open_fds: process.open_files(),
+        limits: procfs::limits(pid),
+        environ: process.environ().iter().map(|v| v.to_string_lossy().to_string()).collect(),
+        sockets,
+        socket_counts: info.sockets,
+    })
+}
+
+/// Everything collected in one tick, as consumed by `App::update`. Recordings store it as JSON.
+#[derive(Clone, Deserialize)]
+pub struct Sample {
+    /// Unix time in milliseconds when the sample was taken
+    pub timestamp_ms: u64,
+    pub cpu: f64,
+    /// Usage of each logical core in percent
+    pub cpu_cores: Vec<f32>,
+    pub memory: (u64, u64),
+    pub disk: (u64, u64),
+    /// Missing from recordings made before per-disk counters were collected
+    #[serde(default)]
+    pub disk_devices: Vec<DiskIo>,
+    pub network: Vec<InterfaceIo>,
+    pub protocols: ProtocolCounters,
+    pub processes: Vec<ProcessInfo>,
+    pub ports: Vec<(u16, String, u32)>,
+    pub cgroups: Vec<CgroupStats>,
+}
+
+#[cfg(test)]
+impl Sample {
+    /// A sample of an idle machine with nothing on it, for tests to fill in.
+    pub fn at(timestamp_ms: u64) -> Self {
+        Self {
+            timestamp_ms,
+            cpu: 0.0,
+            cpu_cores: Vec::new(),
+            memory: (0, 0),
+            disk: (0, 0),
+            disk_devices: Vec::new(),
+            network: Vec::new(),
+            protocols: ProtocolCounters::default(),
+            processes: Vec::new(),
+            ports: Vec::new(),
+            cgroups: Vec::new(),
+        }
     }
+}
 
-    (total_rx, total_tx)
+/// The sysinfo state behind every sample, kept between ticks so CPU usage has a baseline.
+pub struct Collector {
+    pub sys: System,
+    pub disks: Disks,
+    pub networks: Networks,
+    pub users: Users,
 }
 
+impl Collector {
+    pub fn new() -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_all();
+        Self {
+            sys,
+            disks: Disks::new_with_refreshed_list(),
+            networks: Networks::new_with_refreshed_list(),
+            users: Users::new_with_refreshed_list(),
+        }
+    }
+
+    /// Refresh everything and take a sample.
+    pub fn collect(&mut self, config: &Config) -> Sample {
+        self.sys.refresh_all();
+        self.disks.refresh(true);
+        self.networks.refresh(true);
+        collect_sample(&self.sys, &self.disks, &self.networks, &self.users, config)
+    }
+}
+
+/// Read every collector from already-refreshed sysinfo state.
+pub fn collect_sample(sys: &System, disks: &Disks, networks: &Networks, users: &Users, config: &Config) -> Sample {
+    Sample {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        cpu: get_cpu_usage(sys),
+        cpu_cores: sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+        memory: get_memory(sys),
+        disk: get_disk_io(disks),
+        disk_devices: get_disk_devices(),
+        network: get_network_io(networks, &config.network),
+        protocols: get_protocol_counters(),
+        processes: get_processes(sys, users),
+        ports: get_open_ports(sys),
+        cgroups: cgroup::read_cgroups(Path::new(cgroup::CGROUP_ROOT)),
+    }
+}
+
+/// One entry per listening port, for display: the lowest PID holding it.
 pub fn get_open_ports(sys: &System) -> Vec<(u16, String, u32)> {
+    let mut ports = get_port_listeners(sys);
+    ports.dedup_by_key(|(port, _, _)| *port);
+    ports
+}
+
+/// Every process listening on a TCP port, sorted by port and PID. Processes sharing a port
+/// (pre-forked workers, `SO_REUSEPORT`) each get an entry; listeners whose process can't be
+/// seen (other users' without root) are left out.
+pub fn get_port_listeners(sys: &System) -> Vec<(u16, String, u32)> {
     let mut ports: Vec<(u16, String, u32)> = Vec::new();
 
     // Build PID to process name map
@@ -108,21 +635,44 @@ pub fn get_open_ports(sys: &System) -> Vec<(u16, String, u32)> {
         ports = get_ports_linux(&pid_to_name);
     }
 
-    // Sort by port number
-    ports.sort_by_key(|(port, _, _)| *port);
+    // IPv4 and IPv6 sockets of the same process are listed separately
+    ports.sort_by_key(|(port, _, pid)| (*port, *pid));
+    ports.dedup_by_key(|(port, _, pid)| (*port, *pid));
     ports
 }
 
+/// Whether anything listens on TCP `port`. Unlike `get_open_ports` this doesn't need to see the
+/// owning process, so other users' servers count too without root.
+pub fn is_port_listening(port: u16) -> bool {
+    if cfg!(target_os = "linux") {
+        procfs::listening_ports().contains(&port)
+    } else if cfg!(target_os = "macos") {
+        // Format: Proto Recv-Q Send-Q Local-Address Foreign-Address (state), addresses like `*.8080`
+        Command::new("netstat").args(["-an", "-p", "tcp"]).output().is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| line.trim_end().ends_with("LISTEN"))
+                .filter_map(|line| line.split_whitespace().nth(3))
+                .any(|local| local.rsplit('.').next() == Some(port.to_string().as_str()))
+        })
+    } else {
+        false
+    }
+}
+
 pub fn kill_process(pid: u32) -> bool {
-    use std::process::Command;
+    signal_process(pid, "KILL")
+}
 
+/// Send `signal` (a name like `TERM` or a number) to `pid`. Windows only knows forced kills.
+pub fn signal_process(pid: u32, signal: &str) -> bool {
     let result = if cfg!(target_os = "windows") {
         Command::new("taskkill")
             .args(["/PID", &pid.to_string(), "/F"])
             .output()
     } else {
         Command::new("kill")
-            .args(["-9", &pid.to_string()])
+            .args([&format!("-{}", signal), &pid.to_string()])
             .output()
     };
 
@@ -166,10 +716,7 @@ fn get_ports_macos(pid_to_name: &HashMap<u32, String>) -> Vec<(u16, String, u32)
                             .cloned()
                             .unwrap_or_else(|| command.to_string());
 
-                        // Avoid duplicates
-                        if !ports.iter().any(|(p, _, _)| *p == port) {
-                            ports.push((port, process_name, pid));
-                        }
+                        ports.push((port, process_name, pid));
                     }
                 }
             }
@@ -180,61 +727,135 @@ fn get_ports_macos(pid_to_name: &HashMap<u32, String>) -> Vec<(u16, String, u32)
 }
 
 fn get_ports_linux(pid_to_name: &HashMap<u32, String>) -> Vec<(u16, String, u32)> {
-    let mut ports = Vec::new();
-
     // Use ss to get listening ports
-    if let Ok(output) = Command::new("ss")
-        .args(["-tlnp"])
-        .output()
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines().skip(1) {
-            // Skip header
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() < 5 {
-                continue;
-            }
-
-            // Format: State Recv-Q Send-Q Local Address:Port Peer Address:Port Process
-            let local_addr = parts[3];
+    let Ok(output) = Command::new("ss").args(["-tlnp"]).output() else {
+        return Vec::new();
+    };
+    parse_ss_listeners(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .map(|(port, pid)| {
+            let name = pid_to_name.get(&pid).cloned().unwrap_or_else(|| "unknown".to_string());
+            (port, name, pid)
+        })
+        .collect()
+}
 
-            // Extract port
-            if let Some(port_str) = local_addr.rsplit(':').next() {
-                if let Ok(port) = port_str.parse::<u16>() {
-                    // Try to extract PID from process column
-                    let mut pid: u32 = 0;
-                    let process_name = if parts.len() > 5 {
-                        let process_info = parts[5..].join(" ");
-                        // Format: users:(("process",pid=1234,fd=5))
-                        if let Some(start) = process_info.find("pid=") {
-                            let pid_start = start + 4;
-                            let pid_end = process_info[pid_start..]
-                                .find(|c: char| !c.is_numeric())
-                                .map(|i| pid_start + i)
-                                .unwrap_or(process_info.len());
-                            if let Ok(parsed_pid) = process_info[pid_start..pid_end].parse::<u32>() {
-                                pid = parsed_pid;
-                                pid_to_name
-                                    .get(&pid)
-                                    .cloned()
-                                    .unwrap_or_else(|| "unknown".to_string())
-                            } else {
-                                "unknown".to_string()
-                            }
-                        } else {
-                            "unknown".to_string()
-                        }
-                    } else {
-                        "unknown".to_string()
-                    };
+/// (port, PID) of every process in `ss -tlnp` output. Sockets shared by several processes list
+/// them all, and sockets of processes we may not look at list none.
+fn parse_ss_listeners(output: &str) -> Vec<(u16, u32)> {
+    let mut listeners = Vec::new();
+    // Skip header
+    for line in output.lines().skip(1) {
+        // Format: State Recv-Q Send-Q Local Address:Port Peer Address:Port Process
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 5 {
+            continue;
+        }
+        let Some(port) = parts[3].rsplit(':').next().and_then(|p| p.parse::<u16>().ok()) else {
+            continue;
+        };
 
-                    if pid > 0 && !ports.iter().any(|(p, _, _)| *p == port) {
-                        ports.push((port, process_name, pid));
-                    }
-                }
+        // Format: users:(("nginx",pid=1234,fd=6),("nginx",pid=1235,fd=6))
+        let process_info = parts[5..].join(" ");
+        for (start, _) in process_info.match_indices("pid=") {
+            let digits: String = process_info[start + 4..].chars().take_while(char::is_ascii_digit).collect();
+            if let Ok(pid) = digits.parse::<u32>() {
+                listeners.push((port, pid));
             }
         }
     }
+    listeners
+}
 
-    ports
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNMP: &str = "\
+Ip: Forwarding DefaultTTL InReceives
+Ip: 2 64 12974
+Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens PassiveOpens AttemptFails EstabResets CurrEstab InSegs OutSegs RetransSegs InErrs OutRsts InCsumErrors
+Tcp: 1 200 120000 -1 76 48 0 43 2 12914 13378 7 0 18 0
+Udp: InDatagrams NoPorts InErrors OutDatagrams RcvbufErrors SndbufErrors InCsumErrors IgnoredMulti MemErrors
+Udp: 60 0 3 60 5 0 0 0 0
+";
+
+    const NETSTAT: &str = "\
+TcpExt: SyncookiesSent SyncookiesRecv ListenOverflows ListenDrops
+TcpExt: 0 0 11 12
+IpExt: InNoRoutes InTruncatedPkts
+IpExt: 0 0
+";
+
+    #[test]
+    fn parses_snmp_and_netstat_tables() {
+        let mut stats = HashMap::new();
+        parse_snmp_table(SNMP, &mut stats);
+        parse_snmp_table(NETSTAT, &mut stats);
+
+        assert_eq!(stats.get("Tcp.ActiveOpens"), Some(&76));
+        assert_eq!(stats.get("Tcp.RetransSegs"), Some(&7));
+        assert_eq!(stats.get("Tcp.OutRsts"), Some(&18));
+        assert_eq!(stats.get("Udp.InErrors"), Some(&3));
+        assert_eq!(stats.get("Udp.RcvbufErrors"), Some(&5));
+        assert_eq!(stats.get("TcpExt.ListenOverflows"), Some(&11));
+        assert_eq!(stats.get("TcpExt.ListenDrops"), Some(&12));
+        assert_eq!(stats.get("Ip.InReceives"), Some(&12974));
+        // Signed and therefore skipped, without shifting the fields after it
+        assert_eq!(stats.get("Tcp.MaxConn"), None);
+        assert_eq!(stats.get("Tcp.PassiveOpens"), Some(&48));
+    }
+
+    #[test]
+    fn ignores_malformed_lines() {
+        let mut stats = HashMap::new();
+        parse_snmp_table("garbage without colon\nmore garbage\nTcp: ActiveOpens\n", &mut stats);
+        assert!(stats.is_empty());
+    }
+
+    fn label(name: &str, command: &str, cwd: Option<&str>) -> String {
+        let args: Vec<String> = command.split_whitespace().map(String::from).collect();
+        smart_label(name, &args, cwd)
+    }
+
+    #[test]
+    fn labels_inline_code() {
+        assert_eq!(label("python3", "python3 -c import(time);time.sleep(9)", None), "python3 -c <inline>");
+        assert_eq!(label("node", "node -e console.log(1)", Some("/srv/web")), "node -e <inline> (web)");
+        assert_eq!(label("node", "node --no-warnings -p 1+1", None), "node -p <inline>");
+        assert_eq!(label("ruby", "ruby -e puts(1)", None), "ruby -e <inline>");
+    }
+
+    #[test]
+    fn skips_option_values() {
+        assert_eq!(label("python3", "python3 -W ignore worker.py", None), "python3 worker.py");
+        assert_eq!(label("python3", "python3 -X dev -u worker.py", None), "python3 worker.py");
+        assert_eq!(label("java", "java -p mods -jar billing.jar", None), "java billing.jar");
+    }
+
+    #[test]
+    fn labels_entry_points() {
+        assert_eq!(label("npm", "npm run dev", Some("/home/me/shop")), "npm run dev (shop)");
+        assert_eq!(label("node", "node /usr/bin/npm start", Some("/home/me/shop")), "npm start (shop)");
+        assert_eq!(label("python3", "python3 -m http.server 8000", None), "python3 http.server");
+        assert_eq!(label("java", "java -Xmx512m -jar /opt/svc/app.jar", None), "java svc/app.jar");
+        assert_eq!(label("node", "node /srv/api/index.js", Some("/home/me")), "node api/index.js");
+        assert_eq!(label("node", "node index.js", Some("/home/me/shop")), "node shop/index.js");
+        assert_eq!(label("bash", "bash -c sleep", None), "bash");
+    }
+
+    #[test]
+    fn parses_every_ss_listener() {
+        let output = "\
+State  Recv-Q Send-Q Local Address:Port  Peer Address:Port Process
+LISTEN 0      511          0.0.0.0:80         0.0.0.0:*     users:((\"nginx\",pid=1235,fd=6),(\"nginx\",pid=1234,fd=6))
+LISTEN 0      511             [::]:80            [::]:*     users:((\"nginx\",pid=1235,fd=7),(\"nginx\",pid=1234,fd=7))
+LISTEN 0      4096       127.0.0.1:5432       0.0.0.0:*
+LISTEN 0      128                *:3000             *:*     users:((\"node\",pid=77,fd=21))
+";
+        assert_eq!(
+            parse_ss_listeners(output),
+            [(80, 1235), (80, 1234), (80, 1235), (80, 1234), (3000, 77)]
+        );
+    }
 }
@@ -0,0 +1,33 @@
+//! Stop and restart systemd units through `systemctl`. Killing a service's main process just
+//! gets it restarted by systemd, so for unit-owned processes this is usually what's wanted.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+pub fn stop(unit: &str, user: bool) -> Result<()> {
+    systemctl("stop", unit, user)
+}
+
+pub fn restart(unit: &str, user: bool) -> Result<()> {
+    systemctl("restart", unit, user)
+}
+
+/// Run `systemctl [--user] <verb> <unit>`. Without root, polkit may prompt or refuse; the
+/// refusal comes back as the error.
+fn systemctl(verb: &str, unit: &str, user: bool) -> Result<()> {
+    let mut command = Command::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+    // Never wait for an interactive password prompt, the terminal is in raw mode
+    let output = command
+        .args(["--no-ask-password", verb, unit])
+        .output()
+        .context("failed to run systemctl")?;
+
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
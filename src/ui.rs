@@ -1,47 +1,146 @@
+use std::collections::{HashMap, VecDeque};
+
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Gauge, Paragraph, Row, Sparkline, Table},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, Paragraph, Row, Sparkline, Table,
+        TableState, Tabs,
+    },
     Frame,
 };
 
-use crate::app::{App, ConfirmDialog};
+use crate::app::{Action, App, ConfirmDialog, DetailPopup, Focus, Panel, ProcessSort, View, HISTORY_LEN};
+use crate::system::{InterfaceDetails, ProcessDetails, ProcessInfo, ProtocolCounters};
 
 pub fn draw(frame: &mut Frame, app: &App) {
-    let chunks = Layout::default()
+    let outer = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(35),
-            Constraint::Percentage(30),
-            Constraint::Percentage(35),
-        ])
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
         .split(frame.area());
 
-    // Top row: CPU and Memory
-    let top_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[0]);
+    draw_tabs(frame, outer[0], app);
 
-    draw_cpu_panel(frame, top_chunks[0], app);
-    draw_memory_panel(frame, top_chunks[1], app);
+    match app.view {
+        View::Dashboard => draw_dashboard(frame, outer[1], app),
+        View::Processes => draw_processes_panel(frame, outer[1], app),
+        View::Cgroups => draw_cgroups_panel(frame, outer[1], app),
+        View::Users => draw_users_panel(frame, outer[1], app),
+    }
 
-    // Middle row: Disk and Network
-    let middle_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[1]);
+    // Draw confirmation dialog on top if active
+    if let ConfirmDialog::Open { pid, port, process_name, owner, actions, selected } = &app.confirm_dialog {
+        draw_confirm_dialog(frame, *pid, *port, process_name, owner.as_deref(), actions, *selected);
+    }
 
-    draw_disk_panel(frame, middle_chunks[0], app);
-    draw_network_panel(frame, middle_chunks[1], app);
+    match &app.detail_popup {
+        DetailPopup::Interface(details) => draw_interface_popup(frame, details),
+        DetailPopup::Process(details) => draw_process_popup(frame, app, details, app.detail_scroll),
+        DetailPopup::None => {}
+    }
+}
 
-    // Bottom row: Ports (full width)
-    draw_ports_panel(frame, chunks[2], app);
+fn draw_tabs(frame: &mut Frame, area: Rect, app: &App) {
+    let titles: Vec<String> = View::ALL
+        .iter()
+        .enumerate()
+        .map(|(idx, view)| format!("{} {}", idx + 1, view.title()))
+        .collect();
+    let selected = View::ALL.iter().position(|&v| v == app.view).unwrap_or(0);
 
-    // Draw confirmation dialog on top if active
-    if let ConfirmDialog::KillProcess { port, process_name, selected_yes } = &app.confirm_dialog {
-        draw_confirm_dialog(frame, *port, process_name, *selected_yes);
+    let tabs = Tabs::new(titles)
+        .select(selected)
+        .style(Style::default().fg(Color::Gray))
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+    frame.render_widget(tabs, area);
+
+    let mut status = Vec::new();
+    if let Some(message) = &app.status_message {
+        status.push(Span::styled(format!("{} ", message), Style::default().fg(Color::Cyan)));
+    }
+    if app.paused {
+        status.push(Span::styled(
+            " PAUSED ",
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+        let held = match app.held_samples.len() {
+            0 => String::new(),
+            n => format!(" {} new samples,", n),
+        };
+        status.push(Span::raw(format!("{} p to resume ", held)));
+    }
+    if let Some(replay) = &app.replay {
+        let state = if replay.paused { "⏸" } else { "▶" };
+        let time = app.sample_time_ms().map(format_timestamp).unwrap_or_default();
+        status.push(Span::styled(
+            format!(" REPLAY{} ", replay.host.as_deref().map(|h| format!(" {}", h)).unwrap_or_default()),
+            Style::default().fg(Color::Black).bg(Color::Magenta).add_modifier(Modifier::BOLD),
+        ));
+        status.push(Span::raw(format!(
+            " {} {}x  {} UTC  {}/{} ",
+            state,
+            replay.speed,
+            time,
+            replay.position + 1,
+            replay.len
+        )));
+    }
+    if !status.is_empty() {
+        frame.render_widget(Paragraph::new(Line::from(status)).alignment(Alignment::Right), area);
+    }
+}
+
+fn draw_dashboard(frame: &mut Frame, area: Rect, app: &App) {
+    // Wide ports mode gives the whole screen to the ports table
+    if app.ports_wide {
+        draw_ports_panel(frame, area, app);
+        return;
+    }
+
+    // Top row: CPU, Memory and TCP/UDP stats; middle: Disk and Network; bottom: Ports.
+    // Hidden panels give their space to the rest of their row, empty rows to the other rows.
+    let layout: [(u16, &[(Panel, u16)]); 3] = [
+        (35, &[(Panel::Cpu, 40), (Panel::Memory, 25), (Panel::Tcp, 35)]),
+        (30, &[(Panel::Disk, 50), (Panel::Network, 50)]),
+        (35, &[(Panel::Ports, 100)]),
+    ];
+    let rows: Vec<(u16, Vec<(Panel, u16)>)> = layout
+        .iter()
+        .map(|(height, panels)| (*height, panels.iter().copied().filter(|(p, _)| app.shows(*p)).collect::<Vec<_>>()))
+        .filter(|(_, panels)| !panels.is_empty())
+        .collect();
+
+    let row_areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(rows.iter().map(|(height, _)| Constraint::Fill(*height)))
+        .split(area);
+
+    for ((_, panels), row_area) in rows.iter().zip(row_areas.iter()) {
+        let panel_areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(panels.iter().map(|(_, width)| Constraint::Fill(*width)))
+            .split(*row_area);
+        for ((panel, _), panel_area) in panels.iter().zip(panel_areas.iter()) {
+            match panel {
+                Panel::Cpu => draw_cpu_panel(frame, *panel_area, app),
+                Panel::Memory => draw_memory_panel(frame, *panel_area, app),
+                Panel::Tcp => draw_protocol_panel(frame, *panel_area, app),
+                Panel::Disk => draw_disk_panel(frame, *panel_area, app),
+                Panel::Network => draw_network_panel(frame, *panel_area, app),
+                Panel::Ports => draw_ports_panel(frame, *panel_area, app),
+            }
+        }
+    }
+}
+
+fn panel_border_style(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::Cyan)
     }
 }
 
@@ -86,85 +185,317 @@ fn draw_memory_panel(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(gauge, area);
 }
 
-fn draw_disk_panel(frame: &mut Frame, area: Rect, app: &App) {
-    let read_str = format_bytes(app.disk_read);
-    let write_str = format_bytes(app.disk_write);
+/// (label, accessor, whether a non-zero rate indicates trouble)
+type ProtocolMetric = (&'static str, fn(&ProtocolCounters) -> u64, bool);
 
-    let text = vec![
-        Line::from(vec![
-            Span::styled(" Read:  ", Style::default().fg(Color::Gray)),
-            Span::styled(format!("{}/s", read_str), Style::default().fg(Color::Green)),
-        ]),
-        Line::from(vec![
-            Span::styled(" Write: ", Style::default().fg(Color::Gray)),
-            Span::styled(format!("{}/s", write_str), Style::default().fg(Color::Red)),
-        ]),
+fn draw_protocol_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let metrics: [ProtocolMetric; 9] = [
+        ("Retransmits", |p| p.tcp_retransmits, true),
+        ("Active opens", |p| p.tcp_active_opens, false),
+        ("Passive opens", |p| p.tcp_passive_opens, false),
+        ("Estab resets", |p| p.tcp_estab_resets, true),
+        ("RSTs sent", |p| p.tcp_out_resets, false),
+        ("Listen overflow", |p| p.tcp_listen_overflows, true),
+        ("Listen drops", |p| p.tcp_listen_drops, true),
+        ("UDP rcv errors", |p| p.udp_in_errors, true),
+        ("UDP buf errors", |p| p.udp_rcvbuf_errors, true),
     ];
 
-    let paragraph = Paragraph::new(text).block(
+    let label_width = 16;
+    let value_width = 8;
+    let spark_width = area.width.saturating_sub(label_width + value_width + 4) as usize;
+
+    let rows: Vec<Row> = metrics
+        .iter()
+        .map(|(label, value, is_fault)| {
+            let current = value(&app.protocol_rates);
+            let history: Vec<u64> = app.protocol_history.iter().map(value).collect();
+            let value_style = if *is_fault && current > 0 {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Row::new(vec![
+                Cell::from(*label).style(Style::default().fg(Color::Gray)),
+                Cell::from(format!("{}/s", current)).style(value_style),
+                Cell::from(sparkline_text(&history, spark_width)).style(Style::default().fg(Color::Green)),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(label_width),
+        Constraint::Length(value_width),
+        Constraint::Fill(1),
+    ];
+
+    let table = Table::new(rows, widths).block(
         Block::default()
-            .title(" Disk I/O ")
+            .title(" TCP/UDP ")
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Cyan)),
     );
 
-    frame.render_widget(paragraph, area);
+    frame.render_widget(table, area);
 }
 
-fn draw_network_panel(frame: &mut Frame, area: Rect, app: &App) {
-    let rx_str = format_bytes(app.network_rx);
-    let tx_str = format_bytes(app.network_tx);
+/// Render the last `width` values as a one-line bar sparkline, scaled to their maximum.
+fn sparkline_text(values: &[u64], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
 
-    let text = vec![
-        Line::from(vec![
-            Span::styled(" RX: ", Style::default().fg(Color::Gray)),
-            Span::styled(format!("{}/s", rx_str), Style::default().fg(Color::Green)),
-        ]),
-        Line::from(vec![
-            Span::styled(" TX: ", Style::default().fg(Color::Gray)),
-            Span::styled(format!("{}/s", tx_str), Style::default().fg(Color::Yellow)),
-        ]),
+    let values = &values[values.len().saturating_sub(width)..];
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            let level = (v * (BARS.len() as u64 - 1)).checked_div(max).unwrap_or(0);
+            BARS[level as usize]
+        })
+        .collect()
+}
+
+fn draw_disk_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let title = format!(
+        " Disk I/O - Read {}/s, Write {}/s ",
+        format_bytes(app.disk_read),
+        format_bytes(app.disk_write)
+    );
+
+    draw_io_chart(
+        frame,
+        area,
+        title,
+        ("Read", &app.disk_read_history, Color::Green),
+        ("Write", &app.disk_write_history, Color::Red),
+    );
+}
+
+/// Draw two rate series on one chart, `upper` above the axis and `lower` mirrored below it.
+fn draw_io_chart(
+    frame: &mut Frame,
+    area: Rect,
+    title: String,
+    upper: (&str, &VecDeque<u64>, Color),
+    lower: (&str, &VecDeque<u64>, Color),
+) {
+    let (upper_name, upper_history, upper_color) = upper;
+    let (lower_name, lower_history, lower_color) = lower;
+
+    // Right-align both series so the newest sample sits at the right edge
+    let offset = |history: &VecDeque<u64>| (HISTORY_LEN - history.len()) as f64;
+    let upper_data: Vec<(f64, f64)> = upper_history
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| (offset(upper_history) + i as f64, v as f64))
+        .collect();
+    let lower_data: Vec<(f64, f64)> = lower_history
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| (offset(lower_history) + i as f64, -(v as f64)))
+        .collect();
+
+    let peak = upper_history
+        .iter()
+        .chain(lower_history.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1024);
+
+    let datasets = vec![
+        Dataset::default()
+            .name(upper_name)
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(upper_color))
+            .data(&upper_data),
+        Dataset::default()
+            .name(lower_name)
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(lower_color))
+            .data(&lower_data),
     ];
 
-    let paragraph = Paragraph::new(text).block(
-        Block::default()
-            .title(" Network I/O ")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan)),
+    let peak_label = format!("{}/s", format_bytes(peak));
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .x_axis(Axis::default().bounds([0.0, (HISTORY_LEN - 1) as f64]))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([-(peak as f64), peak as f64])
+                .labels(vec![
+                    Span::styled(peak_label.clone(), Style::default().fg(lower_color)),
+                    Span::raw("0"),
+                    Span::styled(peak_label, Style::default().fg(upper_color)),
+                ]),
+        );
+
+    frame.render_widget(chart, area);
+}
+
+fn draw_network_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let title = format!(
+        " Network I/O - RX {}/s, TX {}/s ",
+        format_bytes(app.network_rx),
+        format_bytes(app.network_tx)
+    );
+
+    draw_io_chart(
+        frame,
+        chunks[0],
+        title,
+        ("RX", &app.network_rx_history, Color::Green),
+        ("TX", &app.network_tx_history, Color::Yellow),
     );
 
-    frame.render_widget(paragraph, area);
+    let focused = app.focus == Focus::Network;
+    let rows: Vec<Row> = app
+        .network_interfaces
+        .iter()
+        .enumerate()
+        .map(|(idx, iface)| {
+            let fault_style = if iface.has_faults() {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Row::new(vec![
+                Cell::from(iface.name.clone()),
+                Cell::from(format!("{}/s", format_bytes(iface.rx))).style(Style::default().fg(Color::Green)),
+                Cell::from(format!("{}/s", format_bytes(iface.tx))).style(Style::default().fg(Color::Yellow)),
+                Cell::from(format!("{}/{}", iface.rx_packets, iface.tx_packets)),
+                Cell::from(format!("{}", iface.rx_errors + iface.tx_errors)).style(fault_style),
+                Cell::from(format!("{}", iface.rx_drops + iface.tx_drops)).style(fault_style),
+            ])
+            .style(if focused && idx == app.selected_interface_idx {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else if iface.has_faults() {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            })
+        })
+        .collect();
+
+    let header = Row::new(vec!["Interface", "RX", "TX", "Pkts/s", "Err/s", "Drop/s"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+    let widths = [
+        Constraint::Fill(1),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(13),
+        Constraint::Length(6),
+        Constraint::Length(6),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(if focused { " Interfaces - [Enter] details " } else { " Interfaces " })
+                .borders(Borders::ALL)
+                .border_style(panel_border_style(focused)),
+        )
+        .style(Style::default().fg(Color::White));
+
+    frame.render_widget(table, chunks[1]);
 }
 
 fn draw_ports_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let focused = app.focus == Focus::Ports;
+    let processes: HashMap<u32, &ProcessInfo> = app.processes.iter().map(|p| (p.pid, p)).collect();
+
+    // Port, PID, label and owner are fixed; command and cwd split what's left (column gaps + borders)
+    let (port_width, pid_width, label_width, owner_width) = (7u16, 8u16, 28u16, 22u16);
+    let flexible = area.width.saturating_sub(port_width + pid_width + label_width + owner_width + 7) as usize;
+    // Wide mode wraps instead of truncating, and gives the command all the room the cwds don't need
+    let (command_width, cwd_width) = if app.ports_wide {
+        let longest_cwd = app
+            .open_ports
+            .iter()
+            .filter_map(|(_, _, pid)| processes.get(pid).and_then(|p| p.cwd.as_ref()))
+            .map(|cwd| cwd.chars().count())
+            .max()
+            .unwrap_or(0);
+        let cwd_width = longest_cwd.min(flexible / 3);
+        (flexible - cwd_width, cwd_width)
+    } else {
+        (flexible * 3 / 5, flexible - flexible * 3 / 5)
+    };
+
     let rows: Vec<Row> = app
         .open_ports
         .iter()
         .enumerate()
-        .map(|(idx, (port, name, _pid))| {
-            let style = if idx == app.selected_port_idx {
+        .map(|(idx, (port, name, pid))| {
+            let style = if focused && idx == app.selected_port_idx {
                 Style::default().bg(Color::DarkGray).fg(Color::White)
             } else {
                 Style::default().fg(Color::White)
             };
+            let process = processes.get(pid);
+            let label = process.map(|p| p.label.clone()).unwrap_or_else(|| name.clone());
+            let command = process.map(|p| p.command.clone()).unwrap_or_default();
+            let cwd = process.and_then(|p| p.cwd.clone()).unwrap_or_default();
+            // Published container ports show where they forward to
+            let owner = match app.container_of(*pid, Some(*port)) {
+                Some(container) => match container.ports.iter().find(|p| p.host_port == *port) {
+                    Some(p) => format!("{} →{}/{}", container.name, p.container_port, p.protocol),
+                    None => container.name.clone(),
+                },
+                None => app.owner_label(process.and_then(|p| p.owner.as_ref())),
+            };
+            let (command, cwd) = if app.ports_wide {
+                (wrap(&command, command_width), wrap(&cwd, cwd_width))
+            } else {
+                (truncate(&command, command_width), truncate_left(&cwd, cwd_width))
+            };
+            let height = command.lines().count().max(cwd.lines().count()).max(1) as u16;
             Row::new(vec![
-                format!("{}", port),
-                name.clone(),
+                Cell::from(format!("{}", port)),
+                Cell::from(pid.to_string()),
+                Cell::from(label).style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(owner).style(Style::default().fg(Color::Magenta)),
+                Cell::from(command).style(Style::default().fg(Color::Gray)),
+                Cell::from(cwd).style(Style::default().fg(Color::Gray)),
             ])
+            .height(height)
             .style(style)
         })
         .collect();
 
-    let header = Row::new(vec!["Port", "Process"])
+    let header = Row::new(vec!["Port", "PID", "Process", "Owner", "Command", "Cwd"])
         .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .bottom_margin(1);
 
-    let widths = [Constraint::Length(10), Constraint::Fill(1)];
+    let widths = [
+        Constraint::Length(port_width),
+        Constraint::Length(pid_width),
+        Constraint::Length(label_width),
+        Constraint::Length(owner_width),
+        Constraint::Length(command_width as u16),
+        Constraint::Length(cwd_width as u16),
+    ];
 
     let help_text = if app.open_ports.is_empty() {
         " Open Ports (0) "
+    } else if app.ports_wide {
+        " Open Ports (wide) - [Enter] kill/stop, [i] inspect, [t] show in tree, [w] normal width "
     } else {
-        " Open Ports - [k/j] navigate, [Enter] kill "
+        " Open Ports - [Enter] kill/stop, [i] inspect, [t] show in tree, [g] group, [w] wide, [Tab] switch panel "
     };
 
     let table = Table::new(rows, widths)
@@ -173,18 +504,294 @@ fn draw_ports_panel(frame: &mut Frame, area: Rect, app: &App) {
             Block::default()
                 .title(help_text)
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(panel_border_style(focused)),
         )
         .style(Style::default().fg(Color::White));
 
     frame.render_widget(table, area);
 }
 
-fn draw_confirm_dialog(frame: &mut Frame, port: u16, process_name: &str, selected_yes: bool) {
+/// Cut `text` to `max` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// Break `text` into lines of at most `width` characters.
+fn wrap(text: &str, width: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(width.max(1))
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Like `truncate`, but keeps the end of the text (the informative part of a path).
+fn truncate_left(text: &str, max: usize) -> String {
+    let len = text.chars().count();
+    if len <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(len - max.saturating_sub(1)).collect();
+    format!("…{}", kept)
+}
+
+fn draw_processes_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let mut header_cells: Vec<Cell> = ProcessSort::ALL
+        .iter()
+        .map(|&column| {
+            if column == app.process_sort {
+                let arrow = if app.process_sort_desc { "▼" } else { "▲" };
+                Cell::from(format!("{}{}", column.title(), arrow))
+                    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            } else {
+                Cell::from(column.title())
+            }
+        })
+        .collect();
+    if app.process_tree {
+        // Subtree totals go just before the command column
+        header_cells.insert(8, Cell::from("ΣCPU%"));
+        header_cells.insert(9, Cell::from("ΣMemory"));
+    }
+    let header = Row::new(header_cells)
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+    let rows: Vec<Row> = app
+        .process_view
+        .iter()
+        .enumerate()
+        .map(|(idx, &process_idx)| {
+            let process = &app.processes[process_idx];
+            let style = if idx == app.selected_process_idx {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut cells = vec![
+                process.pid.to_string(),
+                process.user.clone(),
+                format!("{:.1}", process.cpu),
+                format_bytes(process.memory),
+                process.state.clone(),
+                process.threads.to_string(),
+                process.sockets.total.to_string(),
+                app.owner_label(process.owner.as_ref()),
+            ];
+            match app.tree_rows.get(idx) {
+                Some(tree) if app.process_tree => {
+                    let marker = match (tree.has_children, tree.collapsed) {
+                        (false, _) => "  ",
+                        (true, false) => "▾ ",
+                        (true, true) => "▸ ",
+                    };
+                    cells.push(format!("{:.1}", tree.subtree_cpu));
+                    cells.push(format_bytes(tree.subtree_memory));
+                    cells.push(format!("{}{}{}", "  ".repeat(tree.depth), marker, process.command));
+                }
+                _ => cells.push(process.command.clone()),
+            }
+            Row::new(cells).style(style)
+        })
+        .collect();
+
+    let mut widths = vec![
+        Constraint::Length(8),
+        Constraint::Length(12),
+        Constraint::Length(7),
+        Constraint::Length(11),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(22),
+        Constraint::Fill(1),
+    ];
+    if app.process_tree {
+        widths.insert(8, Constraint::Length(7));
+        widths.insert(9, Constraint::Length(11));
+    }
+
+    let mut filter = if app.filter_editing {
+        format!(" Filter: {}_ ", app.process_filter)
+    } else if !app.process_filter.is_empty() {
+        format!(" Filter: {} ", app.process_filter)
+    } else {
+        String::new()
+    };
+    if let Some(user) = &app.user_filter {
+        filter.push_str(&format!(" User: {} [Esc] clear ", user));
+    }
+    let title = format!(
+        " Processes ({}/{}) - [/] filter, [s] sort, [c] top sockets, [r] reverse, [t] tree, [g] group, [i] inspect, [Enter] kill ",
+        app.process_view.len(),
+        app.processes.len()
+    );
+
+    let mut state = TableState::default().with_selected(Some(app.selected_process_idx));
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(title)
+                .title_bottom(Line::from(filter).style(Style::default().fg(Color::Yellow)))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .style(Style::default().fg(Color::White));
+
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_cgroups_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let header = Row::new(vec!["Cgroup", "CPU%", "CPU Limit", "Memory", "Mem Limit", "Mem%", "Read/s", "Write/s"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+    let rows: Vec<Row> = app
+        .cgroups
+        .iter()
+        .enumerate()
+        .map(|(idx, cgroup)| {
+            let style = if idx == app.selected_cgroup_idx {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let memory_percent = match (cgroup.memory_current, cgroup.memory_max) {
+                (Some(current), Some(max)) if max > 0 => Some(current as f64 / max as f64 * 100.0),
+                _ => None,
+            };
+            // Close to a limit means throttling or the OOM killer soon
+            let limit_style = |percent: Option<f64>| match percent {
+                Some(p) if p >= 90.0 => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                Some(p) if p >= 75.0 => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            };
+            let cpu_percent = cgroup.cpu_max.filter(|&max| max > 0.0).map(|max| cgroup.cpu / max * 100.0);
+            let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+            Row::new(vec![
+                Cell::from(format!("{}{}", "  ".repeat(cgroup.depth), cgroup.name())),
+                Cell::from(format!("{:.1}", cgroup.cpu)).style(limit_style(cpu_percent)),
+                Cell::from(or_dash(cgroup.cpu_max.map(|max| format!("{:.0}%", max)))),
+                Cell::from(or_dash(cgroup.memory_current.map(format_bytes))),
+                Cell::from(or_dash(cgroup.memory_max.map(format_bytes))),
+                Cell::from(or_dash(memory_percent.map(|p| format!("{:.1}", p)))).style(limit_style(memory_percent)),
+                Cell::from(format_bytes(cgroup.io_read)),
+                Cell::from(format_bytes(cgroup.io_write)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Fill(1),
+        Constraint::Length(7),
+        Constraint::Length(10),
+        Constraint::Length(11),
+        Constraint::Length(11),
+        Constraint::Length(6),
+        Constraint::Length(11),
+        Constraint::Length(11),
+    ];
+
+    let title = if app.cgroups.is_empty() {
+        " Cgroups - cgroup v2 hierarchy not found ".to_string()
+    } else {
+        format!(" Cgroups ({}) - usage includes children, limits from cpu.max/memory.max ", app.cgroups.len())
+    };
+
+    let mut state = TableState::default().with_selected(Some(app.selected_cgroup_idx));
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .style(Style::default().fg(Color::White));
+
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_users_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let header = Row::new(vec!["User", "UID", "Procs", "CPU%", "Memory", "Mem%", "Listening"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+    let rows: Vec<Row> = app
+        .user_summaries
+        .iter()
+        .enumerate()
+        .map(|(idx, user)| {
+            let style = if idx == app.selected_user_idx {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let memory_percent = if app.memory_total > 0 {
+                user.memory as f64 / app.memory_total as f64 * 100.0
+            } else {
+                0.0
+            };
+            let ports: Vec<String> = user.ports.iter().map(|p| p.to_string()).collect();
+            Row::new(vec![
+                Cell::from(user.name.clone()).style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(user.uid.map(|uid| uid.to_string()).unwrap_or_default()),
+                Cell::from(user.processes.to_string()),
+                Cell::from(format!("{:.1}", user.cpu)),
+                Cell::from(format_bytes(user.memory)),
+                Cell::from(format!("{:.1}", memory_percent)),
+                Cell::from(ports.join(", ")).style(Style::default().fg(Color::Gray)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(16),
+        Constraint::Length(8),
+        Constraint::Length(7),
+        Constraint::Length(8),
+        Constraint::Length(11),
+        Constraint::Length(6),
+        Constraint::Fill(1),
+    ];
+
+    let title = format!(" Users ({}) - [Enter] show processes ", app.user_summaries.len());
+    let mut state = TableState::default().with_selected(Some(app.selected_user_idx));
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .style(Style::default().fg(Color::White));
+
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_confirm_dialog(
+    frame: &mut Frame,
+    pid: u32,
+    port: Option<u16>,
+    process_name: &str,
+    owner: Option<&str>,
+    actions: &[Action],
+    selected: usize,
+) {
     let area = frame.area();
 
     // Center the dialog
-    let dialog_width = 50;
+    let dialog_width = 56;
     let dialog_height = 7;
     let x = (area.width.saturating_sub(dialog_width)) / 2;
     let y = (area.height.saturating_sub(dialog_height)) / 2;
@@ -194,40 +801,56 @@ fn draw_confirm_dialog(frame: &mut Frame, port: u16, process_name: &str, selecte
     // Clear the area behind the dialog
     frame.render_widget(Clear, dialog_area);
 
-    // Build dialog content
-    let yes_style = if selected_yes {
-        Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::White)
+    // "No" first, then one button per action; the highlighted one is filled in
+    let button_style = |idx: usize| match (idx == selected, idx) {
+        (true, 0) => Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD),
+        (true, _) => Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD),
+        (false, _) => Style::default().fg(Color::White),
     };
+    let mut buttons = vec![Span::raw("        "), Span::styled(" No ", button_style(0))];
+    for (idx, action) in actions.iter().enumerate() {
+        buttons.push(Span::raw("     "));
+        buttons.push(Span::styled(format!(" {} ", action.label()), button_style(idx + 1)));
+    }
 
-    let no_style = if !selected_yes {
-        Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::White)
+    let target = match port {
+        Some(port) => format!(" on port {}?", port),
+        None => format!(" (PID {})?", pid),
+    };
+    let highlight = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let question = match (owner, actions.first()) {
+        (Some(owner), Some(Action::Kill(_))) => Line::from(vec![
+            Span::raw("  Kill "),
+            Span::styled(process_name, highlight),
+            Span::raw(" or stop "),
+            Span::styled(owner, highlight),
+            Span::raw(target),
+        ]),
+        (Some(owner), _) => Line::from(vec![
+            Span::raw("  Stop or restart "),
+            Span::styled(owner, highlight),
+            Span::raw(target),
+        ]),
+        (None, _) => Line::from(vec![
+            Span::raw("  Kill process "),
+            Span::styled(process_name, highlight),
+            Span::raw(target),
+        ]),
     };
 
     let text = vec![
         Line::from(""),
-        Line::from(vec![
-            Span::raw("  Kill process "),
-            Span::styled(process_name, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-            Span::raw(format!(" on port {}?", port)),
-        ]),
+        question,
         Line::from(""),
-        Line::from(vec![
-            Span::raw("        "),
-            Span::styled(" No ", no_style),
-            Span::raw("     "),
-            Span::styled(" Yes ", yes_style),
-        ]),
+        Line::from(buttons),
         Line::from(""),
         Line::from(Span::styled("  [Tab] switch  [Enter] confirm  [Esc] cancel", Style::default().fg(Color::DarkGray))),
     ];
 
+    let title = if owner.is_some() { " Confirm Action " } else { " Confirm Kill " };
     let paragraph = Paragraph::new(text).block(
         Block::default()
-            .title(" Confirm Kill ")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Red)),
     );
@@ -235,7 +858,201 @@ fn draw_confirm_dialog(frame: &mut Frame, port: u16, process_name: &str, selecte
     frame.render_widget(paragraph, dialog_area);
 }
 
-fn format_bytes(bytes: u64) -> String {
+fn draw_interface_popup(frame: &mut Frame, details: &InterfaceDetails) {
+    let area = frame.area();
+    let label = |name: &str| Span::styled(format!("  {:<10}", name), Style::default().fg(Color::Gray));
+    let state_style = match details.operstate.as_str() {
+        "up" => Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+        "down" | "lowerlayerdown" => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        _ => Style::default().fg(Color::Yellow),
+    };
+    let speed = details
+        .speed_mbps
+        .map(|mbps| format!("{} Mb/s", mbps))
+        .unwrap_or_else(|| "n/a".to_string());
+
+    let mut text = vec![
+        Line::from(""),
+        Line::from(vec![label("State"), Span::styled(details.operstate.clone(), state_style)]),
+        Line::from(vec![label("MAC"), Span::raw(details.mac.clone())]),
+        Line::from(vec![label("Speed"), Span::raw(speed)]),
+        Line::from(vec![label("Duplex"), Span::raw(details.duplex.clone().unwrap_or_else(|| "n/a".to_string()))]),
+        Line::from(vec![label("MTU"), Span::raw(details.mtu.to_string())]),
+        Line::from(vec![label("Addresses")]),
+    ];
+    if details.addresses.is_empty() {
+        text.push(Line::from(Span::styled("    none", Style::default().fg(Color::DarkGray))));
+    }
+    for addr in &details.addresses {
+        text.push(Line::from(Span::styled(format!("    {}", addr), Style::default().fg(Color::White))));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("  [Esc] close", Style::default().fg(Color::DarkGray))));
+
+    // Every line plus the borders
+    let dialog_width = 60.min(area.width);
+    let dialog_height = (text.len() as u16 + 2).min(area.height);
+    let x = (area.width.saturating_sub(dialog_width)) / 2;
+    let y = (area.height.saturating_sub(dialog_height)) / 2;
+
+    let dialog_area = Rect::new(x, y, dialog_width, dialog_height);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .title(format!(" Interface {} ", details.name))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+fn draw_process_popup(frame: &mut Frame, app: &App, details: &ProcessDetails, scroll: u16) {
+    let area = frame.area();
+
+    let dialog_width = (area.width * 4 / 5).max(60).min(area.width);
+    let dialog_height = (area.height * 4 / 5).max(20).min(area.height);
+    let x = (area.width.saturating_sub(dialog_width)) / 2;
+    let y = (area.height.saturating_sub(dialog_height)) / 2;
+
+    let dialog_area = Rect::new(x, y, dialog_width, dialog_height);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let label = |name: &str| Span::styled(format!("  {:<12}", name), Style::default().fg(Color::Gray));
+    let section = |name: &str| {
+        Line::from(Span::styled(
+            format!("  {}", name),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ))
+    };
+    let dim = |text: String| Line::from(Span::styled(text, Style::default().fg(Color::DarkGray)));
+    let or_unknown = |value: &Option<String>| value.clone().unwrap_or_else(|| "n/a".to_string());
+
+    let mut text = vec![
+        Line::from(""),
+        Line::from(vec![label("Command"), Span::raw(details.command.clone())]),
+        Line::from(vec![label("Executable"), Span::raw(or_unknown(&details.exe))]),
+        Line::from(vec![label("Cwd"), Span::raw(or_unknown(&details.cwd))]),
+        Line::from(vec![label("User/Group"), Span::raw(format!("{} / {}", details.user, details.group))]),
+        Line::from(vec![
+            label("Owner"),
+            Span::raw(match (app.container_of_owner(details.owner.as_ref()), &details.owner) {
+                (Some(container), _) => format!("{} ({}, {})", container.name, container.image, container.state),
+                (None, Some(owner)) => owner.to_string(),
+                (None, None) => "none".to_string(),
+            }),
+        ]),
+        Line::from(vec![
+            label("Parent"),
+            Span::raw(details.parent.map(|p| p.to_string()).unwrap_or_else(|| "none".to_string())),
+        ]),
+        Line::from(vec![
+            label("Started"),
+            Span::raw(format!(
+                "{} UTC ({} ago)",
+                format_timestamp(details.start_time * 1000),
+                format_duration(details.run_time)
+            )),
+        ]),
+        Line::from(vec![label("Threads"), Span::raw(details.threads.to_string())]),
+        Line::from(vec![
+            label("Open FDs"),
+            Span::raw(details.open_fds.map(|n| n.to_string()).unwrap_or_else(|| "n/a".to_string())),
+        ]),
+        Line::from(""),
+        section(&format!("Sockets ({})", details.sockets.len())),
+    ];
+    if details.sockets.is_empty() {
+        text.push(dim("    none".to_string()));
+    } else {
+        let summary: Vec<String> = details
+            .socket_counts
+            .by_state
+            .iter()
+            .map(|(state, count)| format!("{} {}", state, count))
+            .collect();
+        text.push(dim(format!("    {}", summary.join("  "))));
+    }
+    for socket in &details.sockets {
+        text.push(Line::from(format!(
+            "    {:<5} {:<12} {:<30} {}",
+            socket.protocol, socket.state, socket.local, socket.remote
+        )));
+    }
+
+    text.push(Line::from(""));
+    text.push(section("Limits (soft / hard)"));
+    if details.limits.is_empty() {
+        text.push(dim("    n/a".to_string()));
+    }
+    for (name, soft, hard) in &details.limits {
+        text.push(Line::from(format!("    {:<22} {} / {}", name, soft, hard)));
+    }
+
+    text.push(Line::from(""));
+    text.push(section(&format!("Environment ({})", details.environ.len())));
+    if details.environ.is_empty() {
+        text.push(dim("    not readable".to_string()));
+    }
+    for var in &details.environ {
+        text.push(Line::from(format!("    {}", var)));
+    }
+
+    let paragraph = Paragraph::new(text).scroll((scroll, 0)).block(
+        Block::default()
+            .title(format!(" Process {} ({}) ", details.pid, details.name))
+            .title_bottom(Line::from(" [j/k] scroll  [Esc] close ").style(Style::default().fg(Color::DarkGray)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    frame.render_widget(paragraph, dialog_area);
+}
+
+fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Unix milliseconds as `2024-05-01 13:37:00`, in UTC.
+pub fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rest / 3_600,
+        (rest % 3_600) / 60,
+        rest % 60
+    )
+}
+
+pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = 1024 * KB;
     const GB: u64 = 1024 * MB;